@@ -0,0 +1,3 @@
+pub mod render;
+pub mod sweep;
+pub mod wire;
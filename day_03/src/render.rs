@@ -0,0 +1,196 @@
+use crate::wire::{Point, Wire};
+use std::cmp;
+use std::fmt::Write;
+
+const COLOURS: [&str; 6] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#9467bd", "#8c564b", "#e377c2",
+];
+
+struct Bounds {
+    left: i32,
+    top: i32,
+    width: u32,
+    height: u32,
+}
+
+impl Bounds {
+    fn of(wires: &[Wire]) -> Bounds {
+        let points = || wires.iter().flat_map(|wire| wire.points());
+
+        let left = points().map(|p| p.x).min().unwrap_or(0);
+        let right = points().map(|p| p.x).max().unwrap_or(0);
+        let top = points().map(|p| p.y).min().unwrap_or(0);
+        let bottom = points().map(|p| p.y).max().unwrap_or(0);
+
+        Bounds {
+            left,
+            top,
+            width: (right - left) as u32 + 1,
+            height: (bottom - top) as u32 + 1,
+        }
+    }
+}
+
+/// The character wire `index` is drawn with, `1` to `9` then `a` to `z`.
+fn wire_char(index: usize) -> char {
+    std::char::from_digit(index as u32 + 1, 36).unwrap_or('#')
+}
+
+/// Draws the wires as text no more than `max_size` characters wide or lines
+/// tall, shrinking them to fit if needed. Each wire is drawn with its own
+/// character, `+` is where different wires share a character, `X` is a
+/// crossing and `o` is the origin.
+pub fn ascii(wires: &[Wire], crossings: &[Point], max_size: usize) -> String {
+    let bounds = Bounds::of(wires);
+    let size = cmp::max(bounds.width, bounds.height);
+    let scale = cmp::max(size.div_ceil(max_size.max(1) as u32), 1);
+    let columns = bounds.width.div_ceil(scale) as usize;
+    let rows = bounds.height.div_ceil(scale) as usize;
+
+    let cell = |point: &Point| {
+        (
+            (point.x - bounds.left) as u32 / scale,
+            (point.y - bounds.top) as u32 / scale,
+        )
+    };
+
+    let mut grid = vec![vec![' '; columns]; rows];
+    for (index, wire) in wires.iter().enumerate() {
+        let c = wire_char(index);
+
+        for segment in wire.points().windows(2) {
+            let (x1, y1) = cell(&segment[0]);
+            let (x2, y2) = cell(&segment[1]);
+
+            for y in cmp::min(y1, y2)..=cmp::max(y1, y2) {
+                for x in cmp::min(x1, x2)..=cmp::max(x1, x2) {
+                    let square = &mut grid[y as usize][x as usize];
+                    *square = match *square {
+                        ' ' => c,
+                        existing if existing == c => c,
+                        _ => '+',
+                    };
+                }
+            }
+        }
+    }
+
+    for crossing in crossings {
+        let (x, y) = cell(crossing);
+        grid[y as usize][x as usize] = 'X';
+    }
+
+    let (x, y) = cell(&Point { x: 0, y: 0 });
+    grid[y as usize][x as usize] = 'o';
+
+    grid.iter()
+        .map(|row| row.iter().collect::<String>().trim_end().to_string() + "\n")
+        .collect()
+}
+
+/// Draws the wires as an SVG image, with a dot on each crossing and the
+/// origin.
+pub fn svg(wires: &[Wire], crossings: &[Point]) -> String {
+    let bounds = Bounds::of(wires);
+    let size = cmp::max(bounds.width, bounds.height) as f64;
+    let margin = size / 50.0;
+    let radius = (size / 200.0).max(0.5);
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+        bounds.left as f64 - margin,
+        bounds.top as f64 - margin,
+        bounds.width as f64 + margin * 2.0,
+        bounds.height as f64 + margin * 2.0
+    );
+
+    for (index, wire) in wires.iter().enumerate() {
+        let points = wire
+            .points()
+            .iter()
+            .map(|p| format!("{},{}", p.x, p.y))
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        let _ = writeln!(
+            svg,
+            r#"  <polyline points="{}" fill="none" stroke="{}" stroke-width="1" vector-effect="non-scaling-stroke"/>"#,
+            points,
+            COLOURS[index % COLOURS.len()]
+        );
+    }
+
+    for crossing in crossings {
+        let _ = writeln!(
+            svg,
+            r#"  <circle cx="{}" cy="{}" r="{}" fill="red"/>"#,
+            crossing.x, crossing.y, radius
+        );
+    }
+
+    let _ = writeln!(
+        svg,
+        r#"  <circle cx="0" cy="0" r="{}" fill="black"/>"#,
+        radius
+    );
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_ascii() {
+        let wires = [Wire::new("R8,U5,L5,D3"), Wire::new("U7,R6,D4,L4")];
+        let crossings = [Point { x: 3, y: -3 }, Point { x: 6, y: -5 }];
+
+        assert_eq!(
+            ascii(&wires, &crossings, 80),
+            "\
+2222222
+2     2
+2  111X11
+2  1  2 1
+2 2X222 1
+2  1    1
+2       1
+o11111111
+"
+        );
+    }
+
+    #[test]
+    fn shrinks_ascii() {
+        let wires = [Wire::new("R8,U5,L5,D3"), Wire::new("U7,R6,D4,L4")];
+
+        let text = ascii(&wires, &[], 3);
+        assert!(text.lines().all(|line| line.chars().count() <= 3));
+        assert_eq!(text.lines().last(), Some("o11"));
+    }
+
+    #[test]
+    fn shrinks_tall_ascii() {
+        let wires = [Wire::new("U100000000"), Wire::new("R5")];
+
+        let text = ascii(&wires, &[], 10);
+        assert!(text.lines().count() <= 10);
+        assert!(text.lines().all(|line| line.chars().count() <= 10));
+        assert_eq!(text.lines().last(), Some("o"));
+    }
+
+    #[test]
+    fn draws_svg() {
+        let wires = [Wire::new("R8,U5"), Wire::new("U7,R6")];
+        let text = svg(&wires, &[Point { x: 6, y: -5 }]);
+
+        assert!(text.starts_with("<svg "));
+        assert!(text.contains(r#"points="0,0 8,0 8,-5""#));
+        assert!(text.contains(r#"points="0,0 0,-7 6,-7""#));
+        assert!(text.contains(r#"<circle cx="6" cy="-5""#));
+        assert!(text.ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,361 @@
+use crate::wire::{Point, Wire};
+use std::cmp;
+use std::collections::BTreeMap;
+use std::ops::Bound::Excluded;
+
+/// What to do where two wires run along the same line. `get_cross_points`
+/// ignores them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlaps {
+    Ignore,
+    /// Both ends of each overlap are meeting points.
+    Ends,
+    /// Every point along each overlap is a meeting point.
+    All,
+}
+
+/// A point where two or more wires meet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Intersection {
+    pub location: Point,
+    /// Each wire that gets there, in order, with the fewest steps it takes.
+    pub wires: Vec<(usize, u32)>,
+}
+
+impl Intersection {
+    /// The steps every wire takes to get here.
+    pub fn steps(&self) -> u32 {
+        self.wires.iter().map(|(_, steps)| steps).sum()
+    }
+}
+
+struct Segment {
+    wire: usize,
+    start: Point,
+    end: Point,
+    /// The steps the wire took to get to `start`.
+    steps: u32,
+}
+
+impl Segment {
+    fn is_horizontal(&self) -> bool {
+        self.start.y == self.end.y
+    }
+
+    fn left(&self) -> i32 {
+        cmp::min(self.start.x, self.end.x)
+    }
+
+    fn right(&self) -> i32 {
+        cmp::max(self.start.x, self.end.x)
+    }
+
+    fn top(&self) -> i32 {
+        cmp::min(self.start.y, self.end.y)
+    }
+
+    fn bottom(&self) -> i32 {
+        cmp::max(self.start.y, self.end.y)
+    }
+
+    fn steps_to(&self, point: Point) -> u32 {
+        self.steps + ((point.x - self.start.x).abs() + (point.y - self.start.y).abs()) as u32
+    }
+}
+
+// The order events at the same x are handled in. A horizontal segment only
+// counts as crossed strictly between its ends, as in `is_crossing`, so it's
+// removed before and added after the vertical segments at its ends.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Event {
+    Remove,
+    Query,
+    Insert,
+}
+
+/// Finds every point, other than the origin, where two or more of the wires
+/// meet, sorted by location. Perpendicular segments meet where they cross
+/// strictly between their ends, and collinear ones as `overlaps` says.
+///
+/// Crossings are found with a sweep from left to right that keeps the
+/// horizontal segments it's part way along ordered by `y`, so each vertical
+/// segment only looks at the ones that span it.
+pub fn intersections(wires: &[Wire], overlaps: Overlaps) -> Vec<Intersection> {
+    let segments = get_segments(wires);
+    let mut meetings = Vec::new();
+
+    let mut events = Vec::new();
+    for (index, segment) in segments.iter().enumerate() {
+        if segment.is_horizontal() {
+            events.push((segment.left(), Event::Insert, index));
+            events.push((segment.right(), Event::Remove, index));
+        } else {
+            events.push((segment.start.x, Event::Query, index));
+        }
+    }
+    events.sort();
+
+    let mut active = BTreeMap::<i32, Vec<usize>>::new();
+    for (_, event, index) in events {
+        let segment = &segments[index];
+
+        match event {
+            Event::Insert => active.entry(segment.start.y).or_default().push(index),
+            Event::Remove => {
+                let row = active.get_mut(&segment.start.y).unwrap();
+                let position = row.iter().position(|i| *i == index).unwrap();
+                row.swap_remove(position);
+                if row.is_empty() {
+                    active.remove(&segment.start.y);
+                }
+            }
+            Event::Query => {
+                let range = (Excluded(segment.top()), Excluded(segment.bottom()));
+                for (&y, row) in active.range(range) {
+                    for &other in row {
+                        let point = Point {
+                            x: segment.start.x,
+                            y,
+                        };
+                        meet(&mut meetings, segment, &segments[other], point);
+                    }
+                }
+            }
+        }
+    }
+
+    if overlaps != Overlaps::Ignore {
+        find_overlaps(&segments, overlaps, &mut meetings);
+    }
+
+    collect(meetings)
+}
+
+fn get_segments(wires: &[Wire]) -> Vec<Segment> {
+    let mut segments = Vec::new();
+
+    for (wire, points) in wires.iter().map(Wire::points).enumerate() {
+        let mut steps = 0;
+
+        for pair in points.windows(2) {
+            let segment = Segment {
+                wire,
+                start: pair[0],
+                end: pair[1],
+                steps,
+            };
+            steps = segment.steps_to(pair[1]);
+
+            // A zero length segment meets nothing its neighbours don't.
+            if pair[0] != pair[1] {
+                segments.push(segment);
+            }
+        }
+    }
+
+    segments
+}
+
+// A wire passing through a point, and the steps it took to get there.
+type Meeting = (Point, usize, u32);
+
+fn meet(meetings: &mut Vec<Meeting>, a: &Segment, b: &Segment, point: Point) {
+    if a.wire == b.wire || point == (Point { x: 0, y: 0 }) {
+        return;
+    }
+
+    meetings.push((point, a.wire, a.steps_to(point)));
+    meetings.push((point, b.wire, b.steps_to(point)));
+}
+
+/// Finds where segments on the same line share points, by sorting each line's
+/// segments by where they start.
+fn find_overlaps(segments: &[Segment], overlaps: Overlaps, meetings: &mut Vec<Meeting>) {
+    // Keyed by whether the line is horizontal and where it is.
+    let mut lines = BTreeMap::<(bool, i32), Vec<&Segment>>::new();
+    for segment in segments {
+        let key = if segment.is_horizontal() {
+            (true, segment.start.y)
+        } else {
+            (false, segment.start.x)
+        };
+        lines.entry(key).or_default().push(segment);
+    }
+
+    for ((horizontal, line), mut segments) in lines {
+        let span = |segment: &Segment| match horizontal {
+            true => (segment.left(), segment.right()),
+            false => (segment.top(), segment.bottom()),
+        };
+        let point = |along: i32| match horizontal {
+            true => Point { x: along, y: line },
+            false => Point { x: line, y: along },
+        };
+
+        segments.sort_by_key(|segment| span(segment));
+
+        let mut open: Vec<&Segment> = Vec::new();
+        for segment in segments {
+            let (start, end) = span(segment);
+            open.retain(|other| span(other).1 >= start);
+
+            for other in &open {
+                let from = start;
+                let to = cmp::min(end, span(other).1);
+
+                let points = match overlaps {
+                    Overlaps::Ignore => vec![],
+                    Overlaps::Ends if from == to => vec![from],
+                    Overlaps::Ends => vec![from, to],
+                    Overlaps::All => (from..=to).collect(),
+                };
+                for along in points {
+                    meet(meetings, segment, other, point(along));
+                }
+            }
+
+            open.push(segment);
+        }
+    }
+}
+
+/// Groups meetings by point, keeping the fewest steps for each wire.
+fn collect(meetings: Vec<Meeting>) -> Vec<Intersection> {
+    let mut points = BTreeMap::<(i32, i32), BTreeMap<usize, u32>>::new();
+    for (point, wire, steps) in meetings {
+        let wires = points.entry((point.x, point.y)).or_default();
+        let fewest = wires.entry(wire).or_insert(steps);
+        *fewest = cmp::min(*fewest, steps);
+    }
+
+    points
+        .into_iter()
+        .map(|((x, y), wires)| Intersection {
+            location: Point { x, y },
+            wires: wires.into_iter().collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = include_str!("./input.txt");
+
+    fn closest(wires: &[Wire]) -> (u32, u32) {
+        let found = intersections(wires, Overlaps::Ignore);
+
+        (
+            found.iter().map(|i| i.location.distance()).min().unwrap(),
+            found.iter().map(Intersection::steps).min().unwrap(),
+        )
+    }
+
+    #[test]
+    fn examples() {
+        let wires = [Wire::new("R8,U5,L5,D3"), Wire::new("U7,R6,D4,L4")];
+        assert_eq!(closest(&wires), (6, 30));
+
+        let wires = [
+            Wire::new("R75,D30,R83,U83,L12,D49,R71,U7,L72"),
+            Wire::new("U62,R66,U55,R34,D71,R55,D58,R83"),
+        ];
+        assert_eq!(closest(&wires), (159, 610));
+
+        let wires = [
+            Wire::new("R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51"),
+            Wire::new("U98,R91,D20,R16,D67,R40,U7,R15,U6,R7"),
+        ];
+        assert_eq!(closest(&wires), (135, 410));
+    }
+
+    #[test]
+    fn personal() {
+        let wires = INPUT.lines().map(Wire::new).collect::<Vec<Wire>>();
+        assert_eq!(closest(&wires), (5357, 101956));
+    }
+
+    #[test]
+    fn matches_pairwise() {
+        let wires = INPUT.lines().map(Wire::new).collect::<Vec<Wire>>();
+
+        let mut expected = wires[0]
+            .get_cross_points(&wires[1])
+            .iter()
+            .map(|c| (c.location.x, c.location.y))
+            .collect::<Vec<(i32, i32)>>();
+        expected.sort();
+        expected.dedup();
+
+        let found = intersections(&wires, Overlaps::Ignore)
+            .iter()
+            .map(|i| (i.location.x, i.location.y))
+            .collect::<Vec<(i32, i32)>>();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn many_wires() {
+        let wires = [
+            Wire::new("R8,U5,L5,D3"),
+            Wire::new("U7,R6,D4,L4"),
+            Wire::new("U3,R10"),
+        ];
+
+        assert_eq!(
+            intersections(&wires, Overlaps::Ignore),
+            vec![
+                Intersection {
+                    location: Point { x: 3, y: -3 },
+                    wires: vec![(0, 20), (1, 20), (2, 6)],
+                },
+                Intersection {
+                    location: Point { x: 6, y: -5 },
+                    wires: vec![(0, 15), (1, 15)],
+                },
+                Intersection {
+                    location: Point { x: 8, y: -3 },
+                    wires: vec![(0, 11), (2, 11)],
+                },
+            ]
+        );
+        assert_eq!(
+            intersections(&wires, Overlaps::Ignore)[0].steps(),
+            20 + 20 + 6
+        );
+    }
+
+    #[test]
+    fn overlaps() {
+        // They share the line from (2, -1) to (5, -1), where wire 1 has also
+        // crossed wire 0 on its way up.
+        let wires = [Wire::new("U1,R5"), Wire::new("R2,U3,D2,R3")];
+        let locations = |overlaps| {
+            intersections(&wires, overlaps)
+                .iter()
+                .map(|i| (i.location.x, i.location.y, i.steps()))
+                .collect::<Vec<(i32, i32, u32)>>()
+        };
+
+        assert_eq!(locations(Overlaps::Ignore), vec![(2, -1, 6)]);
+        assert_eq!(locations(Overlaps::Ends), vec![(2, -1, 6), (5, -1, 16)]);
+        assert_eq!(
+            locations(Overlaps::All),
+            vec![(2, -1, 6), (3, -1, 12), (4, -1, 14), (5, -1, 16)]
+        );
+    }
+
+    #[test]
+    fn overlaps_ignore_origin_and_same_wire() {
+        let wires = [Wire::new("R4,L2"), Wire::new("R1")];
+
+        assert_eq!(
+            intersections(&wires, Overlaps::All),
+            vec![Intersection {
+                location: Point { x: 1, y: 0 },
+                wires: vec![(0, 1), (1, 1)],
+            }]
+        );
+    }
+}
@@ -1,5 +1,6 @@
 const MAX_ADJACENT_DIGITS: u8 = 2;
 
+#[allow(clippy::manual_range_contains, clippy::get_first)]
 pub fn is_valid(password: &u32) -> bool {
     if password < &100_000 || password > &999_999 {
         return false;
@@ -40,6 +41,7 @@ pub fn is_valid(password: &u32) -> bool {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
 
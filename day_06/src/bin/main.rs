@@ -14,6 +14,7 @@ fn main() {
     println!("Simple checksum: {}", simple_checksum);
 }
 
+#[allow(clippy::redundant_static_lifetimes)]
 const INPUT: &'static str = "WR4)TZN
 M6J)Q1N
 D5B)2KR
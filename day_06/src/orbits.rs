@@ -18,6 +18,7 @@ impl<'a> SpaceObject<'a> {
     }
 }
 
+#[allow(mismatched_lifetime_syntaxes)]
 pub fn parse<'a>(data: &'a str) -> HashMap<&'a str, Rc<RefCell<SpaceObject>>> {
     let mut objects_by_name = HashMap::<&'a str, Rc<RefCell<SpaceObject>>>::new();
 
@@ -50,6 +51,7 @@ pub fn get_checksum(data: &HashMap<&str, Rc<RefCell<SpaceObject>>>) -> u32 {
     data.values().map(|o| get_parent_count(o)).sum()
 }
 
+#[allow(clippy::needless_lifetimes, clippy::explicit_counter_loop)]
 pub fn get_transfers<'a>(
     name_a: &str,
     name_b: &str,
@@ -87,6 +89,7 @@ fn get_parent_count(object: &Rc<RefCell<SpaceObject>>) -> u32 {
     }
 }
 
+#[allow(clippy::single_match)]
 fn get_parent_names<'a>(object: &Rc<RefCell<SpaceObject<'a>>>, names: &mut Vec<&'a str>) {
     // Gone for recursion here to ensure that borrows live all the way through checking other parents
     match &object.borrow().parent {
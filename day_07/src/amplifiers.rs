@@ -0,0 +1,287 @@
+use crate::permutations::*;
+use intcode::*;
+use rayon::prelude::*;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Topology {
+    /// Each amplifier feeds the next once, and the last one gives the signal.
+    Series,
+    /// The last amplifier feeds back into the first until they all halt, and
+    /// the last signal out of the last amplifier is the result.
+    Feedback,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ChainError {
+    /// An amplifier stopped without producing an output.
+    NoOutput { amplifier: usize },
+    Fault {
+        amplifier: usize,
+        error: MachineError,
+    },
+    /// There aren't enough distinct phase settings for the chain.
+    NotEnoughPhases { length: usize, available: usize },
+    /// An amplifier used up its budget before producing an output.
+    BudgetExceeded { amplifier: usize, limit: Limit },
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChainError::NoOutput { amplifier } => {
+                write!(f, "Amplifier {} stopped without an output", amplifier)
+            }
+            ChainError::Fault { amplifier, error } => {
+                write!(f, "Amplifier {} faulted: {}", amplifier, error)
+            }
+            ChainError::NotEnoughPhases { length, available } => write!(
+                f,
+                "A chain of {} needs more than {} phase settings",
+                length, available
+            ),
+            ChainError::BudgetExceeded { amplifier, limit } => {
+                write!(f, "Amplifier {} gave up: {}", amplifier, limit)
+            }
+        }
+    }
+}
+
+impl Error for ChainError {}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Best {
+    pub phases: Vec<i64>,
+    pub signal: i64,
+}
+
+/// Runs a chain of amplifiers, one per phase setting, starting with a signal of 0.
+/// Every amplifier gets its own copy of `budget`.
+pub fn run_chain(
+    program: &[i64],
+    phases: &[i64],
+    topology: Topology,
+    budget: Budget,
+) -> Result<i64, ChainError> {
+    let mut amps: Vec<OpCodeMachine> = phases
+        .iter()
+        .map(|phase| {
+            OpCodeMachine::new(program.to_vec())
+                .with_budget(budget)
+                .with_input(*phase)
+        })
+        .collect();
+
+    let mut signal = 0;
+    loop {
+        for (amplifier, amp) in amps.iter_mut().enumerate() {
+            amp.input(signal);
+
+            signal = match amp.run() {
+                MachineResult::Output(output) => output,
+                MachineResult::Halt if topology == Topology::Feedback && amplifier == 0 => {
+                    return Ok(signal)
+                }
+                MachineResult::Halt | MachineResult::InputRequired => {
+                    return Err(ChainError::NoOutput { amplifier })
+                }
+                MachineResult::BudgetExceeded(limit) => {
+                    return Err(ChainError::BudgetExceeded { amplifier, limit })
+                }
+                MachineResult::Fault(error) => return Err(ChainError::Fault { amplifier, error }),
+            };
+        }
+
+        if topology == Topology::Series {
+            return Ok(signal);
+        }
+    }
+}
+
+/// Tries every way of giving `length` amplifiers distinct phase settings from
+/// `domain`, in parallel, and returns the one with the highest signal. Each
+/// chain is run with `budget`, as in `run_chain`.
+pub fn optimize(
+    program: &[i64],
+    domain: &[i64],
+    length: usize,
+    topology: Topology,
+    budget: Budget,
+) -> Result<Best, ChainError> {
+    if length == 0 || length > domain.len() {
+        return Err(ChainError::NotEnoughPhases {
+            length,
+            available: domain.len(),
+        });
+    }
+
+    // Bridged rather than collected, so only the permutations being run are
+    // held in memory. Each keeps its index so ties go to the first, as they
+    // would in order.
+    let (_, best) = k_permutations(domain, length)
+        .enumerate()
+        .par_bridge()
+        .map(|(index, phases)| {
+            let signal = run_chain(program, &phases, topology, budget)?;
+            Ok((index, Best { phases, signal }))
+        })
+        .try_reduce_with(|a, b| {
+            let a_wins = (a.1.signal, b.0) >= (b.1.signal, a.0);
+            Ok(if a_wins { a } else { b })
+        })
+        .unwrap()?;
+
+    Ok(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERIES_1: [i64; 17] = [
+        3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+    ];
+    const SERIES_2: [i64; 25] = [
+        3, 23, 3, 24, 1002, 24, 10, 24, 1002, 23, -1, 23, 101, 5, 23, 23, 1, 24, 23, 23, 4, 23, 99,
+        0, 0,
+    ];
+    const SERIES_3: [i64; 34] = [
+        3, 31, 3, 32, 1002, 32, 10, 32, 1001, 31, -2, 31, 1007, 31, 0, 33, 1002, 33, 7, 33, 1, 33,
+        31, 31, 1, 32, 31, 31, 4, 31, 99, 0, 0, 0,
+    ];
+    const FEEDBACK_1: [i64; 29] = [
+        3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1, 28,
+        1005, 28, 6, 99, 0, 0, 5,
+    ];
+    const FEEDBACK_2: [i64; 57] = [
+        3, 52, 1001, 52, -5, 52, 3, 53, 1, 52, 56, 54, 1007, 54, 5, 55, 1005, 55, 26, 1001, 54, -5,
+        54, 1105, 1, 12, 1, 53, 54, 53, 1008, 54, 0, 55, 1001, 55, 1, 55, 2, 53, 55, 53, 4, 53,
+        1001, 56, -1, 56, 1005, 56, 6, 99, 0, 0, 0, 0, 10,
+    ];
+
+    fn best(program: &[i64], domain: std::ops::RangeInclusive<i64>, topology: Topology) -> Best {
+        optimize(
+            program,
+            &domain.collect::<Vec<i64>>(),
+            5,
+            topology,
+            Budget::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn series_examples() {
+        assert_eq!(
+            best(&SERIES_1, 0..=4, Topology::Series),
+            Best {
+                phases: vec![4, 3, 2, 1, 0],
+                signal: 43210
+            }
+        );
+        assert_eq!(
+            best(&SERIES_2, 0..=4, Topology::Series),
+            Best {
+                phases: vec![0, 1, 2, 3, 4],
+                signal: 54321
+            }
+        );
+        assert_eq!(
+            best(&SERIES_3, 0..=4, Topology::Series),
+            Best {
+                phases: vec![1, 0, 4, 3, 2],
+                signal: 65210
+            }
+        );
+    }
+
+    #[test]
+    fn feedback_examples() {
+        assert_eq!(
+            best(&FEEDBACK_1, 5..=9, Topology::Feedback),
+            Best {
+                phases: vec![9, 8, 7, 6, 5],
+                signal: 139_629_729
+            }
+        );
+        assert_eq!(
+            best(&FEEDBACK_2, 5..=9, Topology::Feedback),
+            Best {
+                phases: vec![9, 7, 8, 5, 6],
+                signal: 18216
+            }
+        );
+    }
+
+    #[test]
+    fn shorter_chains() {
+        // Three of five phase settings, so 60 assignments to try.
+        let best = optimize(
+            &SERIES_1,
+            &[0, 1, 2, 3, 4],
+            3,
+            Topology::Series,
+            Budget::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            best,
+            Best {
+                phases: vec![4, 3, 2],
+                signal: 432
+            }
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            optimize(&SERIES_1, &[0, 1], 3, Topology::Series, Budget::default()),
+            Err(ChainError::NotEnoughPhases {
+                length: 3,
+                available: 2
+            })
+        );
+        assert_eq!(
+            run_chain(&[3, 0, 99], &[1], Topology::Series, Budget::default()),
+            Err(ChainError::NoOutput { amplifier: 0 })
+        );
+        assert_eq!(
+            run_chain(
+                &[3, 0, 3, 0, 77],
+                &[1, 2],
+                Topology::Feedback,
+                Budget::default()
+            ),
+            Err(ChainError::Fault {
+                amplifier: 0,
+                error: MachineError::InvalidOpCode {
+                    address: 4,
+                    value: 77
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn looping_amplifier() {
+        // Reads its phase setting, then jumps back to the jump forever.
+        let spin = [3, 9, 1105, 1, 2, 99];
+        let budget = Budget::default().steps_per_run(100);
+        assert_eq!(
+            run_chain(&spin, &[0, 1], Topology::Series, budget),
+            Err(ChainError::BudgetExceeded {
+                amplifier: 0,
+                limit: Limit::StepsPerRun
+            })
+        );
+        assert_eq!(
+            optimize(&spin, &[0, 1], 2, Topology::Feedback, budget),
+            Err(ChainError::BudgetExceeded {
+                amplifier: 0,
+                limit: Limit::StepsPerRun
+            })
+        );
+    }
+}
@@ -0,0 +1,2 @@
+pub mod amplifiers;
+pub mod permutations;
@@ -1,56 +1,21 @@
-mod int_code;
-mod permutations;
+use day_7::amplifiers::*;
+use intcode::{program_from_args, Budget};
 
-use crate::permutations::*;
+const INPUT: &str = include_str!("./input.txt");
 
-fn main() {
-    let mut highest = std::i32::MIN;
-
-    for permutation in permutations(&(5..=9).collect()) {
-        let mut amps: Vec<int_code::OpCodeMachine> = permutation
-            .iter()
-            .map(|phase| {
-                let mut amp = int_code::OpCodeMachine::new(AMP_PROGRAM.to_vec());
-                amp.with_input(phase.clone() as i32);
-                amp
-            })
-            .collect();
-
-        let mut input = 0;
-        while !amps.last().unwrap().is_complete {
-            for amp in amps.iter_mut() {
-                if let Some(output) = amp.with_input(input).run() {
-                    input = output
-                }
-            }
-        }
+/// Far more than any amplifier needs, so a broken program fails instead of hanging.
+const STEPS_PER_AMPLIFIER: u64 = 1_000_000;
 
-        if input > highest {
-            highest = input;
-        }
-    }
+fn main() {
+    let program = program_from_args(INPUT);
+    let best = optimize(
+        &program,
+        &(5..=9).collect::<Vec<i64>>(),
+        5,
+        Topology::Feedback,
+        Budget::default().total_steps(STEPS_PER_AMPLIFIER),
+    )
+    .unwrap_or_else(|error| panic!("{}", error));
 
-    println!("Highest: {}", highest);
+    println!("Highest: {}", best.signal);
 }
-
-const AMP_PROGRAM: [i32; 507] = [
-    3, 8, 1001, 8, 10, 8, 105, 1, 0, 0, 21, 34, 51, 64, 81, 102, 183, 264, 345, 426, 99999, 3, 9,
-    102, 2, 9, 9, 1001, 9, 4, 9, 4, 9, 99, 3, 9, 101, 4, 9, 9, 102, 5, 9, 9, 1001, 9, 2, 9, 4, 9,
-    99, 3, 9, 101, 3, 9, 9, 1002, 9, 5, 9, 4, 9, 99, 3, 9, 102, 3, 9, 9, 101, 3, 9, 9, 1002, 9, 4,
-    9, 4, 9, 99, 3, 9, 1002, 9, 3, 9, 1001, 9, 5, 9, 1002, 9, 5, 9, 101, 3, 9, 9, 4, 9, 99, 3, 9,
-    102, 2, 9, 9, 4, 9, 3, 9, 101, 1, 9, 9, 4, 9, 3, 9, 1001, 9, 1, 9, 4, 9, 3, 9, 101, 1, 9, 9, 4,
-    9, 3, 9, 101, 2, 9, 9, 4, 9, 3, 9, 102, 2, 9, 9, 4, 9, 3, 9, 101, 2, 9, 9, 4, 9, 3, 9, 102, 2,
-    9, 9, 4, 9, 3, 9, 102, 2, 9, 9, 4, 9, 3, 9, 102, 2, 9, 9, 4, 9, 99, 3, 9, 101, 2, 9, 9, 4, 9,
-    3, 9, 1001, 9, 1, 9, 4, 9, 3, 9, 1002, 9, 2, 9, 4, 9, 3, 9, 1001, 9, 1, 9, 4, 9, 3, 9, 1001, 9,
-    2, 9, 4, 9, 3, 9, 101, 1, 9, 9, 4, 9, 3, 9, 1002, 9, 2, 9, 4, 9, 3, 9, 102, 2, 9, 9, 4, 9, 3,
-    9, 1002, 9, 2, 9, 4, 9, 3, 9, 101, 1, 9, 9, 4, 9, 99, 3, 9, 1002, 9, 2, 9, 4, 9, 3, 9, 102, 2,
-    9, 9, 4, 9, 3, 9, 102, 2, 9, 9, 4, 9, 3, 9, 101, 1, 9, 9, 4, 9, 3, 9, 101, 2, 9, 9, 4, 9, 3, 9,
-    101, 2, 9, 9, 4, 9, 3, 9, 1002, 9, 2, 9, 4, 9, 3, 9, 1001, 9, 1, 9, 4, 9, 3, 9, 1001, 9, 2, 9,
-    4, 9, 3, 9, 1002, 9, 2, 9, 4, 9, 99, 3, 9, 1001, 9, 1, 9, 4, 9, 3, 9, 102, 2, 9, 9, 4, 9, 3, 9,
-    1002, 9, 2, 9, 4, 9, 3, 9, 101, 2, 9, 9, 4, 9, 3, 9, 101, 2, 9, 9, 4, 9, 3, 9, 1002, 9, 2, 9,
-    4, 9, 3, 9, 102, 2, 9, 9, 4, 9, 3, 9, 1002, 9, 2, 9, 4, 9, 3, 9, 1001, 9, 1, 9, 4, 9, 3, 9,
-    1001, 9, 1, 9, 4, 9, 99, 3, 9, 1002, 9, 2, 9, 4, 9, 3, 9, 102, 2, 9, 9, 4, 9, 3, 9, 1001, 9, 2,
-    9, 4, 9, 3, 9, 101, 2, 9, 9, 4, 9, 3, 9, 102, 2, 9, 9, 4, 9, 3, 9, 1001, 9, 1, 9, 4, 9, 3, 9,
-    1002, 9, 2, 9, 4, 9, 3, 9, 1001, 9, 2, 9, 4, 9, 3, 9, 102, 2, 9, 9, 4, 9, 3, 9, 101, 1, 9, 9,
-    4, 9, 99,
-];
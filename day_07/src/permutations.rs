@@ -1,35 +1,141 @@
-// This seems like a really dumb way to do this.
-// But damn it, Jim, I'm an engineer not a computer scientist/mathematician!
-pub fn permutations<T>(source: &Vec<T>) -> Vec<Vec<T>>
-where
-    T: Clone,
-{
-    if source.len() == 1 {
-        return vec![source.clone()];
-    }
-
-    let mut results = Vec::<Vec<T>>::new();
-    for index in 0..source.len() {
-        let head = source.get(index).unwrap();
-
-        let tail: Vec<T> = source
-            .iter()
-            .enumerate()
-            .filter_map(|(i, v)| if i != index { Some(v.clone()) } else { None })
-            .collect();
-
-        for child_permutation in permutations(&tail) {
-            let mut result = Vec::<T>::new();
-            result.push(head.clone());
-
-            for value in child_permutation {
-                result.push(value);
+/// Iterates over every ordering of `k` items picked from a source, in
+/// lexicographic order of the items' positions in the source. Only one buffer
+/// of indices is kept, so nothing is allocated per permutation unless it's
+/// collected with `next`.
+pub struct Permutations<'a, T> {
+    source: &'a [T],
+    indices: Vec<usize>,
+    k: usize,
+    started: bool,
+    done: bool,
+}
+
+/// Every ordering of all of the items in `source`.
+pub fn permutations<T: Clone>(source: &[T]) -> Permutations<'_, T> {
+    k_permutations(source, source.len())
+}
+
+/// Every ordering of `k` distinct items picked from `source`.
+pub fn k_permutations<T: Clone>(source: &[T], k: usize) -> Permutations<'_, T> {
+    Permutations {
+        source,
+        indices: (0..source.len()).collect(),
+        k,
+        started: false,
+        done: k > source.len(),
+    }
+}
+
+impl<'a, T> Permutations<'a, T> {
+    /// Advances to the next permutation and returns the source positions of
+    /// its items, without allocating.
+    pub fn next_indices(&mut self) -> Option<&[usize]> {
+        if self.done {
+            return None;
+        }
+
+        if self.started {
+            // Reversing the unused tail makes it the largest arrangement of
+            // those indices, so the next full permutation changes the first k.
+            self.indices[self.k..].reverse();
+            if !next_permutation(&mut self.indices) {
+                self.done = true;
+                return None;
+            }
+        }
+
+        self.started = true;
+        Some(&self.indices[..self.k])
+    }
+}
+
+impl<'a, T: Clone> Iterator for Permutations<'a, T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        let source = self.source;
+        self.next_indices()
+            .map(|indices| indices.iter().map(|&i| source[i].clone()).collect())
+    }
+}
+
+/// Iterates over every way of picking `k` items from a source, keeping them in
+/// source order.
+pub struct Combinations<'a, T> {
+    source: &'a [T],
+    indices: Vec<usize>,
+    started: bool,
+    done: bool,
+}
+
+pub fn combinations<T: Clone>(source: &[T], k: usize) -> Combinations<'_, T> {
+    Combinations {
+        source,
+        indices: (0..k).collect(),
+        started: false,
+        done: k > source.len(),
+    }
+}
+
+impl<'a, T> Combinations<'a, T> {
+    /// Advances to the next combination and returns the source positions of
+    /// its items, without allocating.
+    pub fn next_indices(&mut self) -> Option<&[usize]> {
+        if self.done {
+            return None;
+        }
+
+        if self.started {
+            let n = self.source.len();
+            let k = self.indices.len();
+
+            // Find the last index that can still move right, move it, and
+            // pack everything after it up against it.
+            match (0..k).rev().find(|&i| self.indices[i] < n - k + i) {
+                Some(i) => {
+                    self.indices[i] += 1;
+                    for j in i + 1..k {
+                        self.indices[j] = self.indices[j - 1] + 1;
+                    }
+                }
+                None => {
+                    self.done = true;
+                    return None;
+                }
             }
-            results.push(result);
         }
+
+        self.started = true;
+        Some(&self.indices)
+    }
+}
+
+impl<'a, T: Clone> Iterator for Combinations<'a, T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        let source = self.source;
+        self.next_indices()
+            .map(|indices| indices.iter().map(|&i| source[i].clone()).collect())
     }
+}
 
-    results
+/// Rearranges `items` into the next permutation in lexicographic order,
+/// returning false if they were already in the last one.
+fn next_permutation(items: &mut [usize]) -> bool {
+    let pivot = match (1..items.len()).rev().find(|&i| items[i - 1] < items[i]) {
+        Some(i) => i - 1,
+        None => return false,
+    };
+
+    let successor = (pivot + 1..items.len())
+        .rev()
+        .find(|&i| items[i] > items[pivot])
+        .unwrap();
+
+    items.swap(pivot, successor);
+    items[pivot + 1..].reverse();
+    true
 }
 
 #[cfg(test)]
@@ -38,22 +144,88 @@ mod tests {
 
     #[test]
     fn basic_3_items() {
-        let result = permutations(&vec!["A", "B", "C"]);
+        let result: Vec<_> = permutations(&["A", "B", "C"]).collect();
         assert_eq!(result.len(), 6);
         assert!(result.iter().all(|p| p.len() == 3));
     }
 
     #[test]
     fn basic_4_items() {
-        let result = permutations(&vec!["A", "B", "C", "D"]);
+        let result: Vec<_> = permutations(&["A", "B", "C", "D"]).collect();
         assert_eq!(result.len(), 24);
         assert!(result.iter().all(|p| p.len() == 4));
     }
 
     #[test]
     fn basic_5_items() {
-        let result = permutations(&vec!["A", "B", "C", "D", "E"]);
+        let result: Vec<_> = permutations(&["A", "B", "C", "D", "E"]).collect();
         assert_eq!(result.len(), 120);
         assert!(result.iter().all(|p| p.len() == 5));
     }
+
+    #[test]
+    fn lexicographic_order() {
+        let result: Vec<_> = permutations(&[1, 2, 3]).collect();
+        assert_eq!(
+            result,
+            vec![
+                vec![1, 2, 3],
+                vec![1, 3, 2],
+                vec![2, 1, 3],
+                vec![2, 3, 1],
+                vec![3, 1, 2],
+                vec![3, 2, 1]
+            ]
+        );
+    }
+
+    #[test]
+    fn ten_items() {
+        let items = (0..10).collect::<Vec<i32>>();
+        let mut permutations = permutations(&items);
+        let mut count = 0;
+        while permutations.next_indices().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 3_628_800);
+    }
+
+    #[test]
+    fn k_items() {
+        let result: Vec<_> = k_permutations(&['a', 'b', 'c'], 2).collect();
+        assert_eq!(
+            result,
+            vec![
+                vec!['a', 'b'],
+                vec!['a', 'c'],
+                vec!['b', 'a'],
+                vec!['b', 'c'],
+                vec!['c', 'a'],
+                vec!['c', 'b']
+            ]
+        );
+        assert_eq!(k_permutations(&[0; 6], 3).count(), 120);
+        assert_eq!(k_permutations(&[1, 2], 0).count(), 1);
+        assert_eq!(k_permutations(&[1, 2], 3).count(), 0);
+        assert_eq!(permutations::<u8>(&[]).count(), 1);
+    }
+
+    #[test]
+    fn combinations_of() {
+        let result: Vec<_> = combinations(&[1, 2, 3, 4], 2).collect();
+        assert_eq!(
+            result,
+            vec![
+                vec![1, 2],
+                vec![1, 3],
+                vec![1, 4],
+                vec![2, 3],
+                vec![2, 4],
+                vec![3, 4]
+            ]
+        );
+        assert_eq!(combinations(&[0; 10], 4).count(), 210);
+        assert_eq!(combinations(&[1, 2], 0).count(), 1);
+        assert_eq!(combinations(&[1, 2], 3).count(), 0);
+    }
 }
@@ -2,6 +2,7 @@ mod space_image_format;
 use crate::space_image_format::*;
 use colored::*;
 
+#[allow(clippy::print_literal, clippy::print_with_newline)]
 fn main() {
     let image = get_image();
 
@@ -46,6 +47,7 @@ fn get_least_zeros_layer(image: &SpaceImageFormat) -> (usize, usize) {
 }
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use super::*;
 
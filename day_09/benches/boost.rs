@@ -0,0 +1,24 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use day_9::input::DATA;
+use intcode::{parse_program, CachedMachine, MachineResult, OpCodeMachine};
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let program = parse_program(DATA).unwrap();
+
+    c.bench_function("OpCodeMachine::run boost", |b| {
+        b.iter(|| {
+            let mut machine = OpCodeMachine::new(program.clone()).with_input(2);
+            while let MachineResult::Output(_) = machine.run() {}
+        })
+    });
+    c.bench_function("CachedMachine::run boost", |b| {
+        b.iter(|| {
+            let mut machine = CachedMachine::new(program.clone()).with_input(2);
+            while let MachineResult::Output(_) = machine.run() {}
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
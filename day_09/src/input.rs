@@ -0,0 +1 @@
+pub const DATA: &str = include_str!("input.txt");
@@ -15,6 +15,7 @@ pub struct Position {
 }
 
 impl AsteroidMap {
+    #[allow(clippy::get_first)]
     pub fn parse(data: &str) -> AsteroidMap {
         let lines = data.trim().lines().collect::<Vec<&str>>();
         let width = lines.get(0).unwrap().len();
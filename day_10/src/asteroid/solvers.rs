@@ -64,6 +64,7 @@ pub fn get_most_visible<'a>(
     largest.unwrap()
 }
 
+#[allow(mismatched_lifetime_syntaxes)]
 pub fn get_visible_fields<'a>(map: &'a AsteroidMap) -> Vec<(&Position, AsteroidField<'a>)> {
     map.asteroids
         .iter()
@@ -73,6 +74,7 @@ pub fn get_visible_fields<'a>(map: &'a AsteroidMap) -> Vec<(&Position, AsteroidF
 
 pub type AsteroidField<'a> = HashMap<String, Vec<&'a Position>>;
 
+#[allow(clippy::redundant_closure)]
 pub fn get_asteroids_by_angle<'a>(
     map: &'a AsteroidMap,
     position: &'a Position,
@@ -137,6 +139,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::needless_borrow, clippy::get_first)]
     fn get_vaporization_order_data_2() {
         let map = AsteroidMap::parse(test_data::MAP_DATA_2);
 
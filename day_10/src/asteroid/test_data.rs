@@ -1,4 +1,5 @@
 #[cfg(test)]
+#[allow(clippy::redundant_static_lifetimes)]
 pub const MAP_DATA_0: &'static str = "
 .#..#
 .....
@@ -7,6 +8,7 @@ pub const MAP_DATA_0: &'static str = "
 ...##";
 
 #[cfg(test)]
+#[allow(clippy::redundant_static_lifetimes)]
 pub const MAP_DATA_1: &'static str = "
 ......#.#.
 #..#.#....
@@ -20,6 +22,7 @@ pub const MAP_DATA_1: &'static str = "
 .#....####";
 
 #[cfg(test)]
+#[allow(clippy::redundant_static_lifetimes)]
 pub const MAP_DATA_2: &'static str = "
 .#..##.###...#######
 ##.############..##.
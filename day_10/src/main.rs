@@ -2,6 +2,7 @@ mod asteroid;
 use asteroid::map::*;
 use asteroid::solvers::*;
 
+#[allow(clippy::needless_borrow)]
 fn main() {
     let map = AsteroidMap::parse(MAP_DATA);
 
@@ -16,6 +17,7 @@ fn main() {
     );
 }
 
+#[allow(clippy::redundant_static_lifetimes)]
 const MAP_DATA: &'static str = "
 .#......##.#..#.......#####...#..
 ...#.....##......###....#.##.....
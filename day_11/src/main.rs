@@ -1,36 +1,28 @@
-mod int_code;
 mod paint_map;
 
+use intcode::*;
 use paint_map::*;
 
-fn main() {
-    let mut paint_program = int_code::OpCodeMachine::new(DATA.to_vec());
-
-    let mut hull = PaintMap::new();
+const INPUT: &str = include_str!("./input.txt");
 
-    hull.set_current_color(Color::White);
-
-    loop {
-        // Give current colour
-        paint_program.input(color_to_code(hull.get_current_color()));
+fn main() {
+    let mut paint_program = OpCodeMachine::new(program_from_args(INPUT));
 
-        let color = match paint_program.run() {
-            Some(0) => Color::Black,
-            Some(1) => Color::White,
-            None => break,
-            _ => panic!("Unknown color"),
-        };
+    let mut robot = Robot {
+        hull: PaintMap::new(),
+        color: None,
+    };
 
-        let direction = match paint_program.run() {
-            Some(0) => TurnDirection::Left,
-            Some(1) => TurnDirection::Right,
-            _ => panic!("Unknown direction"),
-        };
+    robot.hull.set_current_color(Color::White);
 
-        hull.set_current_color(color);
-        hull.drive(direction);
+    match paint_program.run_with(&mut robot) {
+        MachineResult::Halt => (),
+        MachineResult::Fault(error) => panic!("Machine faulted: {}", error),
+        _ => panic!("Painting stopped early"),
     }
 
+    let hull = robot.hull;
+
     println!(
         "Printed panels count: {}",
         hull.locations_to_color.keys().count()
@@ -39,664 +31,44 @@ fn main() {
     println!("{}", hull.to_ascii());
 }
 
+/// Gives the paint program the current colour and follows its instructions,
+/// which come as a colour to paint and then a direction to turn.
+struct Robot {
+    hull: PaintMap,
+    color: Option<Color>,
+}
+
+impl MachineIo for Robot {
+    fn read(&mut self) -> Option<i64> {
+        Some(color_to_code(self.hull.get_current_color()))
+    }
+
+    fn write(&mut self, value: i64) {
+        match self.color.take() {
+            None => {
+                self.color = match value {
+                    0 => Some(Color::Black),
+                    1 => Some(Color::White),
+                    _ => panic!("Unknown color"),
+                }
+            }
+            Some(color) => {
+                let direction = match value {
+                    0 => TurnDirection::Left,
+                    1 => TurnDirection::Right,
+                    _ => panic!("Unknown direction"),
+                };
+
+                self.hull.set_current_color(color);
+                self.hull.drive(direction);
+            }
+        }
+    }
+}
+
 fn color_to_code(color: &Color) -> i64 {
     match color {
         Color::Black => 0,
         Color::White => 1,
     }
 }
-
-const DATA: [i64; 652] = [
-    3,
-    8,
-    1005,
-    8,
-    330,
-    1106,
-    0,
-    11,
-    0,
-    0,
-    0,
-    104,
-    1,
-    104,
-    0,
-    3,
-    8,
-    102,
-    -1,
-    8,
-    10,
-    1001,
-    10,
-    1,
-    10,
-    4,
-    10,
-    1008,
-    8,
-    1,
-    10,
-    4,
-    10,
-    101,
-    0,
-    8,
-    29,
-    3,
-    8,
-    102,
-    -1,
-    8,
-    10,
-    1001,
-    10,
-    1,
-    10,
-    4,
-    10,
-    1008,
-    8,
-    0,
-    10,
-    4,
-    10,
-    1001,
-    8,
-    0,
-    51,
-    1006,
-    0,
-    78,
-    2,
-    107,
-    9,
-    10,
-    1006,
-    0,
-    87,
-    3,
-    8,
-    1002,
-    8,
-    -1,
-    10,
-    1001,
-    10,
-    1,
-    10,
-    4,
-    10,
-    108,
-    1,
-    8,
-    10,
-    4,
-    10,
-    1001,
-    8,
-    0,
-    82,
-    2,
-    1103,
-    5,
-    10,
-    1,
-    101,
-    8,
-    10,
-    3,
-    8,
-    1002,
-    8,
-    -1,
-    10,
-    1001,
-    10,
-    1,
-    10,
-    4,
-    10,
-    108,
-    0,
-    8,
-    10,
-    4,
-    10,
-    101,
-    0,
-    8,
-    112,
-    1006,
-    0,
-    23,
-    1006,
-    0,
-    20,
-    1,
-    2,
-    11,
-    10,
-    1,
-    1007,
-    12,
-    10,
-    3,
-    8,
-    1002,
-    8,
-    -1,
-    10,
-    1001,
-    10,
-    1,
-    10,
-    4,
-    10,
-    108,
-    1,
-    8,
-    10,
-    4,
-    10,
-    101,
-    0,
-    8,
-    148,
-    3,
-    8,
-    102,
-    -1,
-    8,
-    10,
-    101,
-    1,
-    10,
-    10,
-    4,
-    10,
-    108,
-    1,
-    8,
-    10,
-    4,
-    10,
-    1002,
-    8,
-    1,
-    170,
-    2,
-    101,
-    12,
-    10,
-    2,
-    5,
-    7,
-    10,
-    1,
-    102,
-    10,
-    10,
-    3,
-    8,
-    1002,
-    8,
-    -1,
-    10,
-    1001,
-    10,
-    1,
-    10,
-    4,
-    10,
-    1008,
-    8,
-    1,
-    10,
-    4,
-    10,
-    1001,
-    8,
-    0,
-    205,
-    1,
-    1004,
-    10,
-    10,
-    2,
-    6,
-    13,
-    10,
-    3,
-    8,
-    102,
-    -1,
-    8,
-    10,
-    1001,
-    10,
-    1,
-    10,
-    4,
-    10,
-    1008,
-    8,
-    0,
-    10,
-    4,
-    10,
-    1001,
-    8,
-    0,
-    235,
-    2,
-    102,
-    4,
-    10,
-    1006,
-    0,
-    16,
-    1006,
-    0,
-    84,
-    1006,
-    0,
-    96,
-    3,
-    8,
-    1002,
-    8,
-    -1,
-    10,
-    1001,
-    10,
-    1,
-    10,
-    4,
-    10,
-    108,
-    0,
-    8,
-    10,
-    4,
-    10,
-    1001,
-    8,
-    0,
-    269,
-    1006,
-    0,
-    49,
-    2,
-    1003,
-    6,
-    10,
-    2,
-    1104,
-    14,
-    10,
-    1006,
-    0,
-    66,
-    3,
-    8,
-    102,
-    -1,
-    8,
-    10,
-    101,
-    1,
-    10,
-    10,
-    4,
-    10,
-    108,
-    0,
-    8,
-    10,
-    4,
-    10,
-    1002,
-    8,
-    1,
-    305,
-    2,
-    1,
-    11,
-    10,
-    101,
-    1,
-    9,
-    9,
-    1007,
-    9,
-    1020,
-    10,
-    1005,
-    10,
-    15,
-    99,
-    109,
-    652,
-    104,
-    0,
-    104,
-    1,
-    21102,
-    838479487744,
-    1,
-    1,
-    21102,
-    1,
-    347,
-    0,
-    1106,
-    0,
-    451,
-    21101,
-    666567967640,
-    0,
-    1,
-    21101,
-    358,
-    0,
-    0,
-    1106,
-    0,
-    451,
-    3,
-    10,
-    104,
-    0,
-    104,
-    1,
-    3,
-    10,
-    104,
-    0,
-    104,
-    0,
-    3,
-    10,
-    104,
-    0,
-    104,
-    1,
-    3,
-    10,
-    104,
-    0,
-    104,
-    1,
-    3,
-    10,
-    104,
-    0,
-    104,
-    0,
-    3,
-    10,
-    104,
-    0,
-    104,
-    1,
-    21101,
-    28994219048,
-    0,
-    1,
-    21102,
-    405,
-    1,
-    0,
-    1105,
-    1,
-    451,
-    21102,
-    3375459559,
-    1,
-    1,
-    21101,
-    0,
-    416,
-    0,
-    1106,
-    0,
-    451,
-    3,
-    10,
-    104,
-    0,
-    104,
-    0,
-    3,
-    10,
-    104,
-    0,
-    104,
-    0,
-    21102,
-    838433665892,
-    1,
-    1,
-    21102,
-    1,
-    439,
-    0,
-    1106,
-    0,
-    451,
-    21102,
-    988669698816,
-    1,
-    1,
-    21102,
-    450,
-    1,
-    0,
-    1105,
-    1,
-    451,
-    99,
-    109,
-    2,
-    21201,
-    -1,
-    0,
-    1,
-    21102,
-    1,
-    40,
-    2,
-    21101,
-    482,
-    0,
-    3,
-    21102,
-    472,
-    1,
-    0,
-    1105,
-    1,
-    515,
-    109,
-    -2,
-    2105,
-    1,
-    0,
-    0,
-    1,
-    0,
-    0,
-    1,
-    109,
-    2,
-    3,
-    10,
-    204,
-    -1,
-    1001,
-    477,
-    478,
-    493,
-    4,
-    0,
-    1001,
-    477,
-    1,
-    477,
-    108,
-    4,
-    477,
-    10,
-    1006,
-    10,
-    509,
-    1101,
-    0,
-    0,
-    477,
-    109,
-    -2,
-    2105,
-    1,
-    0,
-    0,
-    109,
-    4,
-    1201,
-    -1,
-    0,
-    514,
-    1207,
-    -3,
-    0,
-    10,
-    1006,
-    10,
-    532,
-    21101,
-    0,
-    0,
-    -3,
-    22102,
-    1,
-    -3,
-    1,
-    21201,
-    -2,
-    0,
-    2,
-    21102,
-    1,
-    1,
-    3,
-    21101,
-    551,
-    0,
-    0,
-    1106,
-    0,
-    556,
-    109,
-    -4,
-    2105,
-    1,
-    0,
-    109,
-    5,
-    1207,
-    -3,
-    1,
-    10,
-    1006,
-    10,
-    579,
-    2207,
-    -4,
-    -2,
-    10,
-    1006,
-    10,
-    579,
-    21201,
-    -4,
-    0,
-    -4,
-    1105,
-    1,
-    647,
-    21201,
-    -4,
-    0,
-    1,
-    21201,
-    -3,
-    -1,
-    2,
-    21202,
-    -2,
-    2,
-    3,
-    21101,
-    0,
-    598,
-    0,
-    1106,
-    0,
-    556,
-    21202,
-    1,
-    1,
-    -4,
-    21102,
-    1,
-    1,
-    -1,
-    2207,
-    -4,
-    -2,
-    10,
-    1006,
-    10,
-    617,
-    21102,
-    0,
-    1,
-    -1,
-    22202,
-    -2,
-    -1,
-    -2,
-    2107,
-    0,
-    -3,
-    10,
-    1006,
-    10,
-    639,
-    22102,
-    1,
-    -1,
-    1,
-    21101,
-    0,
-    639,
-    0,
-    106,
-    0,
-    514,
-    21202,
-    -2,
-    -1,
-    -2,
-    22201,
-    -4,
-    -2,
-    -4,
-    109,
-    -5,
-    2105,
-    1,
-    0,
-];
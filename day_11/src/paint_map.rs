@@ -98,6 +98,7 @@ pub struct Position {
 }
 
 impl Position {
+    #[allow(clippy::assign_op_pattern)]
     pub fn transform(&mut self, heading: &Heading) {
         match heading {
             Heading::North => self.y = self.y + 1,
@@ -20,6 +20,7 @@ impl Vec3 {
     }
 
     #[cfg(test)]
+    #[allow(clippy::inherent_to_string)]
     fn to_string(&self) -> String {
         format!("<x={}, y={}, z={}>", self.x, self.y, self.z)
     }
@@ -51,6 +52,7 @@ impl SpaceObject {
     }
 
     #[cfg(test)]
+    #[allow(clippy::inherent_to_string)]
     fn to_string(&self) -> String {
         format!(
             "pos={}, vel={}",
@@ -60,6 +62,7 @@ impl SpaceObject {
     }
 }
 
+#[allow(clippy::ptr_arg)]
 pub fn update_space_objects(objects: &mut Vec<SpaceObject>) {
     // Update velocities
     for current_index in 0..objects.len() {
@@ -87,6 +90,7 @@ pub fn update_space_objects(objects: &mut Vec<SpaceObject>) {
     }
 }
 
+#[allow(clippy::ptr_arg)]
 pub fn update_space_objects_axis<F, S>(
     objects: &mut Vec<SpaceObject>,
     axis_fetcher: F,
@@ -124,6 +128,7 @@ pub fn update_space_objects_axis<F, S>(
     }
 }
 
+#[allow(clippy::assign_op_pattern)]
 pub fn get_repeat_count_axis<F, S>(
     objects: &mut Vec<SpaceObject>,
     axis_fetcher: &F,
@@ -1,4 +1,4 @@
-use super::int_code::*;
+use intcode::*;
 use std::time::Duration;
 use std::{collections::HashMap, thread};
 
@@ -65,18 +65,24 @@ impl Game {
                 MachineResult::Output(value) => value,
                 MachineResult::InputRequired => return GameTickResult::InputRequired,
                 MachineResult::Halt => return GameTickResult::Halt,
+                MachineResult::Fault(error) => panic!("Machine faulted: {}", error),
+                MachineResult::BudgetExceeded(limit) => panic!("Machine stopped: {}", limit),
             };
 
             let y = match self.machine.run() {
                 MachineResult::Output(value) => value,
                 MachineResult::InputRequired => return GameTickResult::InputRequired,
                 MachineResult::Halt => return GameTickResult::Halt,
+                MachineResult::Fault(error) => panic!("Machine faulted: {}", error),
+                MachineResult::BudgetExceeded(limit) => panic!("Machine stopped: {}", limit),
             };
 
             let tile_id = match self.machine.run() {
                 MachineResult::Output(value) => value,
                 MachineResult::InputRequired => return GameTickResult::InputRequired,
                 MachineResult::Halt => return GameTickResult::Halt,
+                MachineResult::Fault(error) => panic!("Machine faulted: {}", error),
+                MachineResult::BudgetExceeded(limit) => panic!("Machine stopped: {}", limit),
             };
 
             if x < 0 || y < 0 {
@@ -111,7 +117,7 @@ impl Game {
 
         for y in 0..=max_point.y {
             for x in 0..=max_point.x {
-                output.push(self.get_char(&Point { x, y }).clone());
+                output.push(*self.get_char(&Point { x, y }));
             }
 
             output.push('\n');
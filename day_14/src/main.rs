@@ -3,8 +3,10 @@ use std::collections::HashMap;
 
 mod reactions;
 
+#[allow(clippy::redundant_static_lifetimes)]
 const INPUT: &'static str = include_str!("./input");
 
+#[allow(clippy::clone_on_copy)]
 fn main() {
     let reactions_by_output_id = parse_reactions(INPUT).unwrap();
 
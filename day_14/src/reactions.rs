@@ -19,6 +19,7 @@ pub enum Compound {
     Other(String),
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 pub enum ChemicalParseError {
     LineError(String),
@@ -28,6 +29,7 @@ pub enum ChemicalParseError {
 impl FromStr for ReactionChemical {
     type Err = ChemicalParseError;
 
+    #[allow(clippy::bind_instead_of_map)]
     fn from_str(source: &str) -> Result<Self, <Self as FromStr>::Err> {
         match source.split(" ").collect::<Vec<&str>>().as_slice() {
             [left, right] => Ok(Self {
@@ -122,6 +124,7 @@ pub fn get_ore_required_count(
         .sum()
 }
 
+#[allow(clippy::needless_borrow, clippy::assign_op_pattern)]
 pub fn get_fuel_from_ore(
     ore_count: u64,
     reactions_by_output_id: &HashMap<Compound, Reaction>,
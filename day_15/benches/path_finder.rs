@@ -0,0 +1,19 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use day_15::input::INPUT;
+use day_15::path_finder;
+use intcode::{parse_program, OpCodeMachine};
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let machine = OpCodeMachine::new(parse_program(INPUT).unwrap());
+
+    c.bench_function("path_finder::solve (shared pages)", |b| {
+        b.iter(|| path_finder::solve(machine.clone()))
+    });
+    c.bench_function("path_finder::solve (full copies)", |b| {
+        b.iter(|| path_finder::solve_with(machine.clone(), OpCodeMachine::deep_clone))
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
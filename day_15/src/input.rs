@@ -0,0 +1 @@
+pub const INPUT: &str = include_str!("input.txt");
@@ -1,5 +1,5 @@
-use super::int_code::MachineResult;
-use super::int_code::OpCodeMachine;
+use intcode::MachineResult;
+use intcode::OpCodeMachine;
 
 use std::collections::HashMap;
 
@@ -39,13 +39,22 @@ struct PathFinder {
     machine: OpCodeMachine,
 }
 
-pub fn solve(
-    initial_machine: OpCodeMachine,
-) -> (
+type Solution = (
     Option<Vec<Location>>,
     Vec<Location>,
     HashMap<Location, char>,
-) {
+);
+
+pub fn solve(initial_machine: OpCodeMachine) -> Solution {
+    solve_with(initial_machine, OpCodeMachine::clone)
+}
+
+/// Explores the whole map, using `fork` to copy the drone's machine for each
+/// branch of the search.
+pub fn solve_with<F>(initial_machine: OpCodeMachine, fork: F) -> Solution
+where
+    F: Fn(&OpCodeMachine) -> OpCodeMachine,
+{
     let start = PathFinder {
         trail: vec![Location { x: 0, y: 0 }],
         machine: initial_machine,
@@ -75,12 +84,14 @@ pub fn solve(
                     continue;
                 }
 
-                let mut new_machine = path.machine.clone();
+                let mut new_machine = fork(&path.machine);
                 new_machine.input(*direction as i64);
 
                 let move_result = match new_machine.run() {
                     MachineResult::Halt => panic!("The never ending, ended?"),
                     MachineResult::InputRequired => panic!("Machine wanted more input?"),
+                    MachineResult::Fault(error) => panic!("Machine faulted: {}", error),
+                    MachineResult::BudgetExceeded(limit) => panic!("Drone stopped: {}", limit),
                     MachineResult::Output(value) => value.into(),
                 };
 
@@ -115,11 +126,11 @@ pub fn solve(
             }
         }
 
-        if new_paths.len() == 0 {
+        if new_paths.is_empty() {
             break;
         }
 
-        active_paths.extend(new_paths.drain(..));
+        active_paths.append(&mut new_paths);
     }
 
     (oxygen_path, last_path, visited_locations)
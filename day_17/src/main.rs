@@ -1,12 +1,19 @@
-mod int_code;
-
-use int_code::*;
+use intcode::*;
 use std::collections::*;
 
-const INPUT: &'static str = include_str!("./input.txt");
+const INPUT: &str = include_str!("./input.txt");
 
 fn main() {
-    let mut machine = OpCodeMachine::new(parse_code(INPUT));
+    let mut camera = AsciiMachine::new(OpCodeMachine::new(program_from_args(INPUT)));
+
+    let transcript = camera.read_all();
+    match transcript.result {
+        MachineResult::Halt => (),
+        MachineResult::InputRequired => panic!("Input?"),
+        MachineResult::Fault(error) => panic!("Machine faulted: {}", error),
+        MachineResult::BudgetExceeded(limit) => panic!("Machine stopped: {}", limit),
+        MachineResult::Output(_) => unreachable!(),
+    }
 
     let mut scaffold_map = HashSet::<Point>::new();
     let mut points_to_check = Vec::<Point>::new();
@@ -14,43 +21,39 @@ fn main() {
 
     let mut current_consecutive_count = 0;
     let mut current_location = Point { x: 0, y: 0 };
-    loop {
-        match machine.run() {
-            MachineResult::Output(v) => {
-                match v {
-                    35 | 60 | 62 | 94 | 118 => {
-                        scaffold_map.insert(current_location.clone());
-                        current_location.x += 1;
-                        current_consecutive_count += 1;
 
-                        if current_consecutive_count > 2 && current_location.y > 0 {
-                            points_to_check.push(Point {
-                                x: current_location.x - 1,
-                                y: current_location.y,
-                            });
-                        }
-                    }
-                    46 => {
-                        current_location.x += 1;
+    for line in transcript.lines {
+        for c in line.chars() {
+            match c {
+                '#' | '<' | '>' | '^' | 'v' => {
+                    scaffold_map.insert(current_location.clone());
+                    current_location.x += 1;
+                    current_consecutive_count += 1;
 
-                        current_consecutive_count = 0;
+                    if current_consecutive_count > 2 && current_location.y > 0 {
+                        points_to_check.push(Point {
+                            x: current_location.x - 1,
+                            y: current_location.y,
+                        });
                     }
-                    10 => {
-                        if current_location.x > biggest_x {
-                            biggest_x = current_location.x;
-                        }
+                }
+                '.' => {
+                    current_location.x += 1;
 
-                        current_location.x = 0;
-                        current_location.y += 1;
+                    current_consecutive_count = 0;
+                }
+                c => panic!("Unknown value {}", c),
+            };
+        }
 
-                        current_consecutive_count = 0;
-                    }
-                    v => panic!("Unknown value {}", v),
-                };
-            }
-            MachineResult::Halt => break,
-            MachineResult::InputRequired => panic!("Input?"),
+        if current_location.x > biggest_x {
+            biggest_x = current_location.x;
         }
+
+        current_location.x = 0;
+        current_location.y += 1;
+
+        current_consecutive_count = 0;
     }
 
     let result: u32 = points_to_check
@@ -73,19 +76,12 @@ fn main() {
                 }
             );
         }
-        print!("\n");
+        println!();
     }
 
     println!("Result: {}", result);
 }
 
-fn parse_code(value: &str) -> Vec<i64> {
-    value
-        .split(",")
-        .map(|c| c.parse::<i64>().unwrap())
-        .collect()
-}
-
 #[derive(Debug, PartialEq, Hash, Eq, Clone)]
 pub struct Point {
     pub x: u32,
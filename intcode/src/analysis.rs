@@ -0,0 +1,486 @@
+//! Static control-flow analysis. Starting from address 0, instructions are
+//! decoded and followed through every immediate-mode jump target to find the
+//! reachable code, split into basic blocks.
+//!
+//! Jumps whose target is only known at run time (position or relative mode)
+//! can't be followed, so code only reached that way is reported as data.
+
+use crate::disassembler::{decode, Line, Parameter};
+use crate::op_code::{OpCode, ParameterMode};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::ops::Range;
+
+/// How control leaves a basic block.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Exit {
+    Halt,
+    /// Runs on into the next block, which something else jumps to.
+    Next,
+    /// Always jumps to the one successor.
+    Jump,
+    /// Either jumps or carries on to the next instruction.
+    Branch,
+    /// Jumps somewhere that's only known at run time. A conditional jump
+    /// can also carry on to the next instruction.
+    Indirect,
+    /// Jumps to an address outside the program, which faults.
+    OutOfRange(i64),
+    /// Reaches a word that isn't a valid instruction, which faults.
+    Invalid(i64),
+    /// Runs off the end of the program, which faults.
+    OffEnd,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Block {
+    pub start: usize,
+    /// The address just past the last instruction.
+    pub end: usize,
+    /// The address of each instruction in the block.
+    pub instructions: Vec<usize>,
+    pub exit: Exit,
+    /// The start of each block control can go to next.
+    pub successors: Vec<usize>,
+}
+
+/// An instruction that writes to a fixed address holding code.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SelfModifyingWrite {
+    pub address: usize,
+    pub target: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    program: Vec<i64>,
+    code: Vec<bool>,
+    pub blocks: Vec<Block>,
+    pub self_modifying: Vec<SelfModifyingWrite>,
+}
+
+// A decoded, reachable instruction and where it can go next.
+struct Instruction {
+    op_code: OpCode,
+    parameters: Vec<Parameter>,
+    exit: Option<Exit>,
+    successors: Vec<usize>,
+}
+
+/// Analyses a program, as given to `OpCodeMachine::new`.
+pub fn analyze(program: &[i64]) -> Analysis {
+    let mut instructions = BTreeMap::new();
+    let mut invalid = BTreeMap::new();
+
+    let mut pending = if program.is_empty() { vec![] } else { vec![0] };
+    while let Some(address) = pending.pop() {
+        if address >= program.len()
+            || instructions.contains_key(&address)
+            || invalid.contains_key(&address)
+        {
+            continue;
+        }
+
+        match decode(&program[address..], address) {
+            Line::Instruction {
+                op_code,
+                parameters,
+                ..
+            } => {
+                let instruction = follow(address, op_code, parameters, program.len());
+                pending.extend(&instruction.successors);
+                instructions.insert(address, instruction);
+            }
+            Line::Data { value, .. } => {
+                invalid.insert(address, value);
+            }
+        }
+    }
+
+    let mut code = vec![false; program.len()];
+    for (address, instruction) in &instructions {
+        for word in &mut code[*address..*address + instruction.op_code.size()] {
+            *word = true;
+        }
+    }
+
+    let mut leaders = BTreeSet::new();
+    if !program.is_empty() {
+        leaders.insert(0);
+    }
+    for instruction in instructions.values() {
+        if instruction.exit.is_some() {
+            leaders.extend(&instruction.successors);
+        }
+    }
+
+    let blocks = leaders
+        .iter()
+        .map(|&start| build_block(start, &instructions, &invalid, &leaders))
+        .collect();
+
+    let self_modifying = instructions
+        .iter()
+        .filter_map(|(&address, instruction)| {
+            let target = written_address(instruction)?;
+            if code.get(target) == Some(&true) {
+                Some(SelfModifyingWrite { address, target })
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    Analysis {
+        program: program.to_vec(),
+        code,
+        blocks,
+        self_modifying,
+    }
+}
+
+fn follow(address: usize, op_code: OpCode, parameters: Vec<Parameter>, len: usize) -> Instruction {
+    let next = address + op_code.size();
+
+    let jumps_if = match op_code {
+        OpCode::JumpIfTrue(..) => Some(true),
+        OpCode::JumpIfFalse(..) => Some(false),
+        _ => None,
+    };
+
+    let (exit, successors) = match (op_code, jumps_if) {
+        (OpCode::Halt, _) => (Some(Exit::Halt), vec![]),
+        (_, Some(jumps_if)) => {
+            // A constant condition makes the jump either always or never taken.
+            let taken = match &parameters[0] {
+                Parameter {
+                    mode: ParameterMode::Immediate,
+                    value,
+                } => Some((*value != 0) == jumps_if),
+                _ => None,
+            };
+
+            let target = match &parameters[1] {
+                Parameter {
+                    mode: ParameterMode::Immediate,
+                    value,
+                } if *value >= 0 && (*value as usize) < len => Ok(*value as usize),
+                Parameter {
+                    mode: ParameterMode::Immediate,
+                    value,
+                } => Err(Exit::OutOfRange(*value)),
+                _ => Err(Exit::Indirect),
+            };
+
+            match (taken, target) {
+                (Some(false), _) => (None, vec![next]),
+                (Some(true), Ok(target)) => (Some(Exit::Jump), vec![target]),
+                (Some(true), Err(exit)) => (Some(exit), vec![]),
+                (None, Ok(target)) => (Some(Exit::Branch), vec![target, next]),
+                (None, Err(exit)) => (Some(exit), vec![next]),
+            }
+        }
+        _ => (None, vec![next]),
+    };
+
+    let successors = successors
+        .into_iter()
+        .filter(|&successor| successor < len)
+        .collect();
+
+    Instruction {
+        op_code,
+        parameters,
+        exit,
+        successors,
+    }
+}
+
+fn build_block(
+    start: usize,
+    instructions: &BTreeMap<usize, Instruction>,
+    invalid: &BTreeMap<usize, i64>,
+    leaders: &BTreeSet<usize>,
+) -> Block {
+    let mut addresses = Vec::new();
+    let mut address = start;
+
+    let (exit, successors) = loop {
+        let instruction = match instructions.get(&address) {
+            Some(instruction) => instruction,
+            None => break (Exit::Invalid(invalid[&address]), vec![]),
+        };
+        addresses.push(address);
+
+        if let Some(exit) = instruction.exit {
+            break (exit, instruction.successors.clone());
+        }
+
+        address += instruction.op_code.size();
+        if !instructions.contains_key(&address) && !invalid.contains_key(&address) {
+            break (Exit::OffEnd, vec![]);
+        }
+        if leaders.contains(&address) {
+            break (Exit::Next, vec![address]);
+        }
+    };
+
+    let end = match addresses.last() {
+        Some(last) => last + instructions[last].op_code.size(),
+        None => start,
+    };
+
+    Block {
+        start,
+        end,
+        instructions: addresses,
+        exit,
+        successors,
+    }
+}
+
+// The address an instruction writes to, if it's known without running it.
+fn written_address(instruction: &Instruction) -> Option<usize> {
+    let parameter = match instruction.op_code {
+        OpCode::Add(..) | OpCode::Multiply(..) | OpCode::LessThan(..) | OpCode::Equals(..) => {
+            &instruction.parameters[2]
+        }
+        OpCode::Input(..) => &instruction.parameters[0],
+        _ => return None,
+    };
+
+    match parameter {
+        Parameter {
+            mode: ParameterMode::Position,
+            value,
+        } if *value >= 0 => Some(*value as usize),
+        _ => None,
+    }
+}
+
+impl Analysis {
+    /// Whether `address` is part of an instruction that can be reached.
+    pub fn is_code(&self, address: usize) -> bool {
+        self.code.get(address) == Some(&true)
+    }
+
+    pub fn code_ranges(&self) -> Vec<Range<usize>> {
+        self.ranges(true)
+    }
+
+    /// Everything that isn't reachable code.
+    pub fn data_ranges(&self) -> Vec<Range<usize>> {
+        self.ranges(false)
+    }
+
+    fn ranges(&self, is_code: bool) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+
+        for (address, _) in self.code.iter().enumerate().filter(|(_, c)| **c == is_code) {
+            match ranges.last_mut() {
+                Some(range) if range.end == address => range.end += 1,
+                _ => ranges.push(address..address + 1),
+            }
+        }
+
+        ranges
+    }
+
+    pub fn block(&self, start: usize) -> Option<&Block> {
+        self.blocks.iter().find(|block| block.start == start)
+    }
+
+    /// The control-flow graph in Graphviz DOT format. Each block is labelled
+    /// with its disassembly, and edges for carrying on to the next
+    /// instruction are dashed.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph program {{").unwrap();
+        writeln!(dot, "    node [shape=box, fontname=\"monospace\"];").unwrap();
+
+        for block in &self.blocks {
+            let mut label = String::new();
+            for address in &block.instructions {
+                write!(label, "{}\\l", decode(&self.program[*address..], *address)).unwrap();
+            }
+
+            match block.exit {
+                Exit::Indirect => label.push_str("-> ?\\l"),
+                Exit::OutOfRange(target) => write!(label, "-> {} (outside)\\l", target).unwrap(),
+                Exit::Invalid(value) => {
+                    write!(label, "{:>5}: DATA {}\\l", block.end, value).unwrap()
+                }
+                Exit::OffEnd => label.push_str("-> end of program\\l"),
+                _ => (),
+            }
+
+            writeln!(dot, "    b{} [label=\"{}\"];", block.start, label).unwrap();
+        }
+
+        for block in &self.blocks {
+            for successor in &block.successors {
+                let style = if *successor == block.end && block.exit != Exit::Jump {
+                    " [style=dashed]"
+                } else {
+                    ""
+                };
+                writeln!(dot, "    b{} -> b{}{};", block.start, successor, style).unwrap();
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::assemble;
+
+    #[test]
+    fn straight_line() {
+        let analysis = analyze(&[1101, 1, 2, 5, 99, 0]);
+
+        assert_eq!(
+            analysis.blocks,
+            vec![Block {
+                start: 0,
+                end: 5,
+                instructions: vec![0, 4],
+                exit: Exit::Halt,
+                successors: vec![],
+            }]
+        );
+        assert_eq!(analysis.code_ranges(), vec![0..5]);
+        assert_eq!(analysis.data_ranges(), vec![5..6]);
+        assert!(analysis.self_modifying.is_empty());
+    }
+
+    #[test]
+    fn loop_blocks() {
+        // Counts down from 3, outputting each number.
+        let program = assemble(
+            "
+                ADD  #3, #0, [n]
+            loop:
+                OUT  [n]
+                ADD  [n], #-1, [n]
+                JT   [n], #loop
+                HLT
+            n:  data 0",
+        )
+        .unwrap();
+        let analysis = analyze(&program);
+
+        let starts = analysis.blocks.iter().map(|b| b.start).collect::<Vec<_>>();
+        assert_eq!(starts, vec![0, 4, 13]);
+
+        assert_eq!(analysis.block(0).unwrap().exit, Exit::Next);
+        assert_eq!(analysis.block(0).unwrap().successors, vec![4]);
+        assert_eq!(analysis.block(4).unwrap().exit, Exit::Branch);
+        assert_eq!(analysis.block(4).unwrap().successors, vec![4, 13]);
+        assert_eq!(analysis.block(13).unwrap().exit, Exit::Halt);
+        assert_eq!(analysis.data_ranges(), vec![14..15]);
+    }
+
+    #[test]
+    fn jump_over_data() {
+        let program = assemble(
+            "
+                JT   #1, #end
+                data 1, 2, 3
+            end:
+                HLT",
+        )
+        .unwrap();
+        let analysis = analyze(&program);
+
+        assert_eq!(analysis.block(0).unwrap().exit, Exit::Jump);
+        assert_eq!(analysis.block(0).unwrap().successors, vec![6]);
+        assert_eq!(analysis.code_ranges(), vec![0..3, 6..7]);
+        assert_eq!(analysis.data_ranges(), vec![3..6]);
+        assert!(!analysis.is_code(4));
+    }
+
+    #[test]
+    fn never_taken() {
+        let analysis = analyze(&[1105, 0, 7, 99]);
+
+        assert_eq!(analysis.blocks.len(), 1);
+        assert_eq!(analysis.blocks[0].instructions, vec![0, 3]);
+    }
+
+    #[test]
+    fn faults() {
+        assert_eq!(
+            analyze(&[1105, 1, 4, 99, 77]).block(4).unwrap().exit,
+            Exit::Invalid(77)
+        );
+        assert_eq!(analyze(&[1105, 1, 40]).blocks[0].exit, Exit::OutOfRange(40));
+        assert_eq!(analyze(&[104, 1]).blocks[0].exit, Exit::OffEnd);
+        assert!(analyze(&[]).blocks.is_empty());
+    }
+
+    #[test]
+    fn indirect() {
+        let program = assemble(
+            "
+                JF   [x], [x]
+                HLT
+            x:  data 0",
+        )
+        .unwrap();
+        let analysis = analyze(&program);
+
+        assert_eq!(analysis.block(0).unwrap().exit, Exit::Indirect);
+        assert_eq!(analysis.block(0).unwrap().successors, vec![3]);
+    }
+
+    #[test]
+    fn self_modifying() {
+        let program = assemble(
+            "
+            start:
+                ADD  #3, #4, [out]
+                OUT  [out]
+                JT   [done], #end
+                ADD  #1, #0, [done]
+                ADD  #1102, #0, [start]
+                JT   #1, #start
+            end: HLT
+            out: data 0
+            done: data 0",
+        )
+        .unwrap();
+        let analysis = analyze(&program);
+
+        assert_eq!(
+            analysis.self_modifying,
+            vec![SelfModifyingWrite {
+                address: 13,
+                target: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn dot() {
+        let dot = analyze(&[1105, 1, 4, 99, 1006, 0, 3, 99]).to_dot();
+
+        assert_eq!(
+            dot,
+            "digraph program {
+    node [shape=box, fontname=\"monospace\"];
+    b0 [label=\"    0: JT   #1, #4\\l\"];
+    b3 [label=\"    3: HLT\\l\"];
+    b4 [label=\"    4: JF   [0], #3\\l\"];
+    b7 [label=\"    7: HLT\\l\"];
+    b0 -> b4;
+    b4 -> b3;
+    b4 -> b7 [style=dashed];
+}
+"
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use crate::machine::{MachineResult, OpCodeMachine};
+use crate::trace::{NoTracer, Tracer};
+use std::error::Error;
+use std::fmt;
+
+const NEWLINE: i64 = 10;
+
+/// Something an `AsciiMachine` read back from its program.
+#[derive(Debug, PartialEq)]
+pub enum AsciiOutput {
+    /// A line of text, without its newline.
+    Line(String),
+    /// An output that isn't an ASCII code, such as a puzzle answer.
+    Value(i64),
+    /// The machine stopped for any reason other than producing output.
+    Stopped(MachineResult),
+}
+
+/// Everything an `AsciiMachine` produced before it stopped.
+#[derive(Debug, PartialEq)]
+pub struct Transcript {
+    pub lines: Vec<String>,
+    pub values: Vec<i64>,
+    pub result: MachineResult,
+}
+
+/// A line given to `send_line` had a character that isn't ASCII at
+/// `position`, counted in characters.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct NotAscii {
+    pub position: usize,
+    pub character: char,
+}
+
+impl fmt::Display for NotAscii {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "'{}' at position {} isn't ASCII",
+            self.character, self.position
+        )
+    }
+}
+
+impl Error for NotAscii {}
+
+/// Talks to a program that reads and writes text, one ASCII code at a time.
+pub struct AsciiMachine<T = NoTracer> {
+    machine: OpCodeMachine<T>,
+    line: String,
+    stopped: Option<MachineResult>,
+}
+
+impl<T: Tracer> AsciiMachine<T> {
+    pub fn new(machine: OpCodeMachine<T>) -> AsciiMachine<T> {
+        AsciiMachine {
+            machine,
+            line: String::new(),
+            stopped: None,
+        }
+    }
+
+    pub fn machine(&self) -> &OpCodeMachine<T> {
+        &self.machine
+    }
+
+    pub fn machine_mut(&mut self) -> &mut OpCodeMachine<T> {
+        &mut self.machine
+    }
+
+    pub fn into_machine(self) -> OpCodeMachine<T> {
+        self.machine
+    }
+
+    /// Queues the codes for `line` followed by a newline. Nothing is queued
+    /// if any of `line` isn't ASCII.
+    pub fn send_line(&mut self, line: &str) -> Result<(), NotAscii> {
+        if let Some((position, character)) = line.chars().enumerate().find(|(_, c)| !c.is_ascii()) {
+            return Err(NotAscii {
+                position,
+                character,
+            });
+        }
+
+        for byte in line.bytes() {
+            self.machine.input(byte as i64);
+        }
+        self.machine.input(NEWLINE);
+        Ok(())
+    }
+
+    /// Runs until there's a whole line or a non-ASCII value to return, or the
+    /// machine stops. A line the machine stops part way through is returned
+    /// as it is, before the reason it stopped.
+    pub fn read(&mut self) -> AsciiOutput {
+        if let Some(result) = self.stopped.take() {
+            return AsciiOutput::Stopped(result);
+        }
+
+        loop {
+            match self.machine.run() {
+                MachineResult::Output(NEWLINE) => {
+                    return AsciiOutput::Line(self.line.split_off(0));
+                }
+                MachineResult::Output(value) if (0..128).contains(&value) => {
+                    self.line.push(value as u8 as char);
+                }
+                MachineResult::Output(value) => return AsciiOutput::Value(value),
+                result if self.line.is_empty() => return AsciiOutput::Stopped(result),
+                result => {
+                    self.stopped = Some(result);
+                    return AsciiOutput::Line(self.line.split_off(0));
+                }
+            }
+        }
+    }
+
+    /// Reads until the machine stops.
+    pub fn read_all(&mut self) -> Transcript {
+        let mut lines = Vec::new();
+        let mut values = Vec::new();
+
+        loop {
+            match self.read() {
+                AsciiOutput::Line(line) => lines.push(line),
+                AsciiOutput::Value(value) => values.push(value),
+                AsciiOutput::Stopped(result) => {
+                    return Transcript {
+                        lines,
+                        values,
+                        result,
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::assemble;
+
+    fn echo() -> AsciiMachine {
+        let program = assemble(
+            "
+            loop:
+                IN   [c]
+                OUT  [c]
+                JT   #1, #loop
+            c:  data 0",
+        )
+        .unwrap();
+
+        AsciiMachine::new(OpCodeMachine::new(program))
+    }
+
+    #[test]
+    fn echoes_lines() {
+        let mut machine = echo();
+        machine.send_line("Hello").unwrap();
+        machine.send_line("").unwrap();
+        machine.send_line("A,B,C").unwrap();
+
+        assert_eq!(machine.read(), AsciiOutput::Line("Hello".to_string()));
+        assert_eq!(machine.read(), AsciiOutput::Line("".to_string()));
+        assert_eq!(machine.read(), AsciiOutput::Line("A,B,C".to_string()));
+        assert_eq!(
+            machine.read(),
+            AsciiOutput::Stopped(MachineResult::InputRequired)
+        );
+    }
+
+    #[test]
+    fn values() {
+        let mut machine = echo();
+        machine.send_line("Hi").unwrap();
+        machine.machine_mut().input(1_000_000);
+        machine.machine_mut().input(-1);
+        machine.send_line("there").unwrap();
+
+        assert_eq!(
+            machine.read_all(),
+            Transcript {
+                lines: vec!["Hi".to_string(), "there".to_string()],
+                values: vec![1_000_000, -1],
+                result: MachineResult::InputRequired,
+            }
+        );
+    }
+
+    #[test]
+    fn partial_line() {
+        let mut machine = echo();
+        machine.machine_mut().input(b'?' as i64);
+
+        assert_eq!(machine.read(), AsciiOutput::Line("?".to_string()));
+        assert_eq!(
+            machine.read(),
+            AsciiOutput::Stopped(MachineResult::InputRequired)
+        );
+
+        machine.send_line("ok").unwrap();
+        assert_eq!(machine.read(), AsciiOutput::Line("ok".to_string()));
+    }
+
+    #[test]
+    fn rejects_non_ascii() {
+        let mut machine = echo();
+
+        assert_eq!(
+            machine.send_line("café"),
+            Err(NotAscii {
+                position: 3,
+                character: 'é'
+            })
+        );
+        assert!(machine.machine().inputs().is_empty());
+    }
+
+    #[test]
+    fn halts() {
+        let mut machine = AsciiMachine::new(OpCodeMachine::new(vec![104, 65, 104, 10, 99]));
+
+        assert_eq!(machine.read(), AsciiOutput::Line("A".to_string()));
+        assert_eq!(machine.read(), AsciiOutput::Stopped(MachineResult::Halt));
+        assert_eq!(machine.read(), AsciiOutput::Stopped(MachineResult::Halt));
+    }
+}
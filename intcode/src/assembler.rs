@@ -0,0 +1,497 @@
+use crate::op_code::*;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub struct AssemblyError {
+    pub line: usize,
+    pub kind: AssemblyErrorKind,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AssemblyErrorKind {
+    UnknownMnemonic(String),
+    ParameterCount { expected: usize, found: usize },
+    InvalidParameter(String),
+    InvalidLabel(String),
+    DuplicateLabel(String),
+    UnknownLabel(String),
+    AddressMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+
+        match &self.kind {
+            AssemblyErrorKind::UnknownMnemonic(mnemonic) => {
+                write!(f, "unknown mnemonic '{}'", mnemonic)
+            }
+            AssemblyErrorKind::ParameterCount { expected, found } => {
+                write!(f, "expected {} parameter(s) but found {}", expected, found)
+            }
+            AssemblyErrorKind::InvalidParameter(parameter) => {
+                write!(f, "invalid parameter '{}'", parameter)
+            }
+            AssemblyErrorKind::InvalidLabel(label) => write!(f, "invalid label '{}'", label),
+            AssemblyErrorKind::DuplicateLabel(label) => {
+                write!(f, "label '{}' is already defined", label)
+            }
+            AssemblyErrorKind::UnknownLabel(label) => write!(f, "unknown label '{}'", label),
+            AssemblyErrorKind::AddressMismatch { expected, found } => write!(
+                f,
+                "address {} was given but this line is at {}",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl Error for AssemblyError {}
+
+enum Operand {
+    Literal(i64),
+    Label(String),
+    /// The negated address of a label, from `rb-label`.
+    NegatedLabel(String),
+}
+
+enum Statement {
+    Instruction {
+        line: usize,
+        mnemonic: String,
+        parameters: Vec<(ParameterMode, Operand)>,
+    },
+    Data {
+        line: usize,
+        values: Vec<Operand>,
+    },
+}
+
+/// Assembles the textual form of a program into the memory image that
+/// `OpCodeMachine::new` takes. The syntax matches the disassembler's listing:
+///
+/// ```text
+/// ; Outputs 1 if the input is 8, otherwise 0
+/// start:  IN   [value]
+///         EQ   [value], #8, [value]
+///         OUT  [value]
+///         JT   #0, #start      ; never taken
+///         HLT
+/// value:  data 0
+/// ```
+///
+/// Parameters are `[x]` for position mode, `#x` for immediate mode and `rb+x`
+/// (or `rb-x`) for relative mode, where `x` is a number or a label. A numeric
+/// label such as `12:` asserts the address of the line rather than naming it.
+pub fn assemble(source: &str) -> Result<Vec<i64>, AssemblyError> {
+    let mut labels = HashMap::<String, usize>::new();
+    let mut statements = Vec::<Statement>::new();
+    let mut address = 0;
+
+    for (index, text) in source.lines().enumerate() {
+        let line = index + 1;
+        let error = |kind| AssemblyError { line, kind };
+
+        let mut rest = text.split(';').next().unwrap().trim();
+
+        while let Some(colon) = rest.find(':') {
+            let label = rest[..colon].trim();
+            if label.contains(char::is_whitespace) || label.contains(',') {
+                break;
+            }
+
+            if let Ok(found) = label.parse::<usize>() {
+                if found != address {
+                    return Err(error(AssemblyErrorKind::AddressMismatch {
+                        expected: address,
+                        found,
+                    }));
+                }
+            } else if !is_label(label) {
+                return Err(error(AssemblyErrorKind::InvalidLabel(label.to_string())));
+            } else if labels.insert(label.to_string(), address).is_some() {
+                return Err(error(AssemblyErrorKind::DuplicateLabel(label.to_string())));
+            }
+
+            rest = rest[colon + 1..].trim();
+        }
+
+        if rest.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, arguments) = match rest.find(char::is_whitespace) {
+            Some(split) => (&rest[..split], rest[split..].trim()),
+            None => (rest, ""),
+        };
+
+        let arguments: Vec<&str> = if arguments.is_empty() {
+            Vec::new()
+        } else {
+            arguments.split(',').map(|a| a.trim()).collect()
+        };
+
+        let statement = if mnemonic.eq_ignore_ascii_case("data") {
+            let values = arguments
+                .iter()
+                .map(|a| parse_operand(a).ok_or_else(|| invalid_parameter(line, a)))
+                .collect::<Result<Vec<Operand>, AssemblyError>>()?;
+
+            address += values.len();
+            Statement::Data { line, values }
+        } else {
+            let expected = match parameter_count(mnemonic) {
+                Some(count) => count,
+                None => {
+                    return Err(error(AssemblyErrorKind::UnknownMnemonic(
+                        mnemonic.to_string(),
+                    )))
+                }
+            };
+
+            if arguments.len() != expected {
+                return Err(error(AssemblyErrorKind::ParameterCount {
+                    expected,
+                    found: arguments.len(),
+                }));
+            }
+
+            let parameters = arguments
+                .iter()
+                .map(|a| parse_parameter(a).ok_or_else(|| invalid_parameter(line, a)))
+                .collect::<Result<Vec<(ParameterMode, Operand)>, AssemblyError>>()?;
+
+            address += parameters.len() + 1;
+            Statement::Instruction {
+                line,
+                mnemonic: mnemonic.to_uppercase(),
+                parameters,
+            }
+        };
+
+        statements.push(statement);
+    }
+
+    let mut program = Vec::with_capacity(address);
+
+    for statement in statements {
+        match statement {
+            Statement::Instruction {
+                line,
+                mnemonic,
+                parameters,
+            } => {
+                let modes: Vec<ParameterMode> = parameters.iter().map(|(mode, _)| *mode).collect();
+                program.push(build_op_code(&mnemonic, &modes).encode());
+
+                for (_, operand) in parameters {
+                    program.push(resolve(&operand, &labels, line)?);
+                }
+            }
+            Statement::Data { line, values } => {
+                for operand in values {
+                    program.push(resolve(&operand, &labels, line)?);
+                }
+            }
+        }
+    }
+
+    Ok(program)
+}
+
+fn parameter_count(mnemonic: &str) -> Option<usize> {
+    match mnemonic.to_uppercase().as_str() {
+        "ADD" | "MUL" | "LT" | "EQ" => Some(3),
+        "JT" | "JF" => Some(2),
+        "IN" | "OUT" | "ARB" => Some(1),
+        "HLT" => Some(0),
+        _ => None,
+    }
+}
+
+fn build_op_code(mnemonic: &str, modes: &[ParameterMode]) -> OpCode {
+    match (mnemonic, modes) {
+        ("ADD", [p1, p2, p3]) => OpCode::Add(*p1, *p2, *p3),
+        ("MUL", [p1, p2, p3]) => OpCode::Multiply(*p1, *p2, *p3),
+        ("IN", [p1]) => OpCode::Input(*p1),
+        ("OUT", [p1]) => OpCode::Output(*p1),
+        ("JT", [p1, p2]) => OpCode::JumpIfTrue(*p1, *p2),
+        ("JF", [p1, p2]) => OpCode::JumpIfFalse(*p1, *p2),
+        ("LT", [p1, p2, p3]) => OpCode::LessThan(*p1, *p2, *p3),
+        ("EQ", [p1, p2, p3]) => OpCode::Equals(*p1, *p2, *p3),
+        ("ARB", [p1]) => OpCode::AdjustRelativeBase(*p1),
+        ("HLT", []) => OpCode::Halt,
+        _ => unreachable!("parameter counts are checked while parsing"),
+    }
+}
+
+fn parse_parameter(text: &str) -> Option<(ParameterMode, Operand)> {
+    if text.starts_with('[') && text.ends_with(']') {
+        Some((
+            ParameterMode::Position,
+            parse_operand(&text[1..text.len() - 1])?,
+        ))
+    } else if let Some(operand) = text.strip_prefix('#') {
+        Some((ParameterMode::Immediate, parse_operand(operand)?))
+    } else if let Some(offset) = text.strip_prefix("rb") {
+        let label = |sign| {
+            offset
+                .strip_prefix(sign)
+                .map(str::trim)
+                .filter(|text| is_label(text))
+        };
+        let operand = match (label('+'), label('-')) {
+            (Some(label), _) => Operand::Label(label.to_string()),
+            (_, Some(label)) => Operand::NegatedLabel(label.to_string()),
+            _ if offset.is_empty() => Operand::Literal(0),
+            _ => Operand::Literal(offset.parse().ok()?),
+        };
+
+        Some((ParameterMode::Relative, operand))
+    } else {
+        None
+    }
+}
+
+fn parse_operand(text: &str) -> Option<Operand> {
+    let text = text.trim();
+
+    if let Ok(value) = text.parse::<i64>() {
+        Some(Operand::Literal(value))
+    } else if is_label(text) {
+        Some(Operand::Label(text.to_string()))
+    } else {
+        None
+    }
+}
+
+fn resolve(
+    operand: &Operand,
+    labels: &HashMap<String, usize>,
+    line: usize,
+) -> Result<i64, AssemblyError> {
+    match operand {
+        Operand::Literal(value) => Ok(*value),
+        Operand::Label(label) => {
+            labels
+                .get(label)
+                .map(|address| *address as i64)
+                .ok_or_else(|| AssemblyError {
+                    line,
+                    kind: AssemblyErrorKind::UnknownLabel(label.clone()),
+                })
+        }
+        Operand::NegatedLabel(label) => {
+            resolve(&Operand::Label(label.clone()), labels, line).map(|address| -address)
+        }
+    }
+}
+
+fn is_label(text: &str) -> bool {
+    let mut chars = text.chars();
+
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+fn invalid_parameter(line: usize, text: &str) -> AssemblyError {
+    AssemblyError {
+        line,
+        kind: AssemblyErrorKind::InvalidParameter(text.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    mod assemble {
+        use super::super::*;
+        use crate::machine::*;
+
+        #[test]
+        fn equal_to_8_position() {
+            let source = "
+                ; Outputs 1 if the input is 8, otherwise 0
+                IN   [value]
+                EQ   [value], [eight], [value]
+                OUT  [value]
+                HLT
+            value: data -1
+            eight: data 8";
+
+            assert_eq!(
+                assemble(source),
+                Ok(vec![3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8])
+            );
+        }
+
+        #[test]
+        fn jumps_to_labels() {
+            let source = "
+                IN   [counter]
+            loop:
+                JF   [counter], #end
+                OUT  [counter]
+                ADD  [counter], #-1, [counter]
+                JT   #1, #loop
+            end: HLT
+            counter: data 0";
+
+            let program = assemble(source).unwrap();
+            assert_eq!(
+                program,
+                vec![3, 15, 1006, 15, 14, 4, 15, 1001, 15, -1, 15, 1105, 1, 2, 99, 0]
+            );
+
+            let mut machine = OpCodeMachine::new(program).with_input(3);
+            assert_eq!(machine.run(), MachineResult::Output(3));
+            assert_eq!(machine.run(), MachineResult::Output(2));
+            assert_eq!(machine.run(), MachineResult::Output(1));
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+
+        #[test]
+        fn relative_parameters() {
+            assert_eq!(
+                assemble("ARB #5\nOUT rb-5\nADD rb+1, rb, rb+end\nend: hlt"),
+                Ok(vec![109, 5, 204, -5, 22201, 1, 0, 8, 99])
+            );
+            assert_eq!(
+                assemble("ARB #end\nOUT rb-end\nend: hlt"),
+                Ok(vec![109, 4, 204, -4, 99])
+            );
+        }
+
+        #[test]
+        fn data_values() {
+            assert_eq!(
+                assemble("start: data 1, -2, start, end\nend: data"),
+                Ok(vec![1, -2, 0, 4])
+            );
+        }
+
+        #[test]
+        fn address_assertions() {
+            assert_eq!(assemble("0: HLT\n1: data 5"), Ok(vec![99, 5]));
+            assert_eq!(
+                assemble("HLT\n2: data 5"),
+                Err(AssemblyError {
+                    line: 2,
+                    kind: AssemblyErrorKind::AddressMismatch {
+                        expected: 1,
+                        found: 2
+                    }
+                })
+            );
+        }
+    }
+
+    mod errors {
+        use super::super::*;
+
+        fn kind(source: &str) -> (usize, AssemblyErrorKind) {
+            let error = assemble(source).unwrap_err();
+            (error.line, error.kind)
+        }
+
+        #[test]
+        fn unknown_mnemonic() {
+            assert_eq!(
+                kind("HLT\nNOP"),
+                (2, AssemblyErrorKind::UnknownMnemonic("NOP".to_string()))
+            );
+        }
+
+        #[test]
+        fn parameter_count() {
+            assert_eq!(
+                kind("\n\nADD [1], [2]"),
+                (
+                    3,
+                    AssemblyErrorKind::ParameterCount {
+                        expected: 3,
+                        found: 2
+                    }
+                )
+            );
+        }
+
+        #[test]
+        fn invalid_parameter() {
+            assert_eq!(
+                kind("OUT 5"),
+                (1, AssemblyErrorKind::InvalidParameter("5".to_string()))
+            );
+            assert_eq!(
+                kind("data 1, 2x"),
+                (1, AssemblyErrorKind::InvalidParameter("2x".to_string()))
+            );
+        }
+
+        #[test]
+        fn labels() {
+            assert_eq!(
+                kind("a: HLT\na: HLT"),
+                (2, AssemblyErrorKind::DuplicateLabel("a".to_string()))
+            );
+            assert_eq!(
+                kind("HLT\nJT #1, #nowhere"),
+                (2, AssemblyErrorKind::UnknownLabel("nowhere".to_string()))
+            );
+            assert_eq!(
+                kind("1a: HLT"),
+                (1, AssemblyErrorKind::InvalidLabel("1a".to_string()))
+            );
+        }
+
+        #[test]
+        fn display() {
+            let error = assemble("HLT\nNOP").unwrap_err();
+            assert_eq!(error.to_string(), "line 2: unknown mnemonic 'NOP'");
+        }
+    }
+
+    mod round_trip {
+        use super::super::*;
+        use crate::disassembler::listing;
+
+        fn round_trip(program: &[i64]) {
+            assert_eq!(assemble(&listing(program)), Ok(program.to_vec()));
+        }
+
+        #[test]
+        fn equal_to_8() {
+            round_trip(&[3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]);
+            round_trip(&[3, 3, 1108, -1, 8, 3, 4, 3, 99]);
+        }
+
+        #[test]
+        fn large_example() {
+            round_trip(&[
+                3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36,
+                98, 0, 0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000,
+                1, 20, 4, 20, 1105, 1, 46, 98, 99,
+            ]);
+        }
+
+        #[test]
+        fn quine() {
+            round_trip(&[
+                109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+            ]);
+        }
+
+        #[test]
+        fn relative_examples() {
+            round_trip(&[109, 1, 9, 2, 204, -6, 99]);
+            round_trip(&[109, 1, 209, -1, 204, -106, 99]);
+            round_trip(&[109, 1, 203, 2, 204, 2, 99]);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use intcode::analysis::analyze;
+use intcode::load_program;
+use std::{env, process};
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<String>>();
+    let (path, dot) = match args.as_slice() {
+        [path] => (path, false),
+        [path, flag] if flag == "--dot" => (path, true),
+        _ => {
+            eprintln!("Usage: analyze <program file> [--dot]");
+            process::exit(1);
+        }
+    };
+
+    let program = load_program(path).unwrap_or_else(|error| {
+        eprintln!("Unable to load {}: {}", path, error);
+        process::exit(1);
+    });
+
+    let analysis = analyze(&program);
+    if dot {
+        print!("{}", analysis.to_dot());
+        return;
+    }
+
+    println!("Blocks:");
+    for block in &analysis.blocks {
+        println!(
+            "  {:>5}..{:<5} {:?} -> {:?}",
+            block.start, block.end, block.exit, block.successors
+        );
+    }
+
+    println!("Code: {:?}", analysis.code_ranges());
+    println!("Data: {:?}", analysis.data_ranges());
+
+    for write in &analysis.self_modifying {
+        println!(
+            "Self-modifying write at {} to {}",
+            write.address, write.target
+        );
+    }
+}
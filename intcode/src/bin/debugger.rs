@@ -0,0 +1,54 @@
+use intcode::debugger::{Command, Debugger};
+use intcode::{load_program, OpCodeMachine};
+use std::io::{self, BufRead, Write};
+use std::{env, process};
+
+fn main() {
+    let path = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: debugger <program file>");
+            process::exit(1);
+        }
+    };
+
+    let program = load_program(&path).unwrap_or_else(|error| {
+        eprintln!("Unable to load {}: {}", path, error);
+        process::exit(1);
+    });
+
+    let mut debugger = Debugger::new(OpCodeMachine::new(program));
+    let mut last_command = Command::List(1);
+
+    print!("{}", debugger.execute(&last_command));
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        print!("(debug) ");
+        io::stdout().flush().unwrap();
+
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => break,
+        };
+
+        // An empty line repeats the last command, like gdb
+        if !line.trim().is_empty() {
+            last_command = match line.parse() {
+                Ok(command) => command,
+                Err(error) => {
+                    println!("{}", error);
+                    continue;
+                }
+            };
+        }
+
+        if last_command == Command::Quit {
+            break;
+        }
+
+        print!("{}", debugger.execute(&last_command));
+    }
+}
@@ -0,0 +1,20 @@
+use intcode::disassembler;
+use intcode::load_program;
+use std::{env, process};
+
+fn main() {
+    let path = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: disassemble <program file>");
+            process::exit(1);
+        }
+    };
+
+    let program = load_program(&path).unwrap_or_else(|error| {
+        eprintln!("Unable to load {}: {}", path, error);
+        process::exit(1);
+    });
+
+    print!("{}", disassembler::listing(&program));
+}
@@ -0,0 +1,54 @@
+use intcode::profiler::Profiler;
+use intcode::{load_program, MachineResult, OpCodeMachine};
+use std::{env, process};
+
+const HOT_SPOTS: usize = 20;
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let path = match args.next() {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: profile <program file> [inputs...]");
+            process::exit(1);
+        }
+    };
+
+    let program = load_program(&path).unwrap_or_else(|error| {
+        eprintln!("Unable to load {}: {}", path, error);
+        process::exit(1);
+    });
+
+    let mut machine = OpCodeMachine::new(program).with_tracer(Profiler::new());
+    for arg in args {
+        let input = arg.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid input: {}", arg);
+            process::exit(1);
+        });
+        machine.input(input);
+    }
+
+    let mut outputs = Vec::new();
+    loop {
+        match machine.run() {
+            MachineResult::Output(value) => outputs.push(value),
+            MachineResult::Halt => break,
+            MachineResult::InputRequired => {
+                eprintln!("Stopped waiting for input");
+                break;
+            }
+            MachineResult::Fault(error) => {
+                eprintln!("Machine faulted: {}", error);
+                break;
+            }
+            MachineResult::BudgetExceeded(limit) => {
+                eprintln!("Machine stopped: {}", limit);
+                break;
+            }
+        }
+    }
+
+    println!("Outputs: {:?}", outputs);
+    println!();
+    print!("{}", machine.tracer().report(machine.memory(), HOT_SPOTS));
+}
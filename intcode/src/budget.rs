@@ -0,0 +1,51 @@
+use std::fmt;
+
+/// Limits on how much work an `OpCodeMachine` may do, so a program that spins
+/// without input or output can't hang its caller. Nothing is limited by
+/// default.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Budget {
+    /// The most instructions a single call to `run` may execute.
+    pub steps_per_run: Option<u64>,
+    /// The most instructions the machine may ever execute.
+    pub total_steps: Option<u64>,
+    /// The most addresses memory may grow to hold.
+    pub memory: Option<usize>,
+}
+
+impl Budget {
+    pub fn steps_per_run(mut self, steps: u64) -> Budget {
+        self.steps_per_run = Some(steps);
+        self
+    }
+
+    pub fn total_steps(mut self, steps: u64) -> Budget {
+        self.total_steps = Some(steps);
+        self
+    }
+
+    pub fn memory(mut self, size: usize) -> Budget {
+        self.memory = Some(size);
+        self
+    }
+}
+
+/// Which part of a `Budget` ran out. The instruction that would have gone over
+/// isn't executed, so the machine can carry on once the budget is raised, or
+/// for `StepsPerRun`, by calling `run` again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Limit {
+    StepsPerRun,
+    TotalSteps,
+    Memory,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Limit::StepsPerRun => write!(f, "Ran out of steps for this run"),
+            Limit::TotalSteps => write!(f, "Ran out of steps"),
+            Limit::Memory => write!(f, "Ran out of memory"),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::budget::Budget;
+use crate::machine::OpCodeMachine;
+use crate::memory::{Memory, MemoryModel};
+use crate::patch::Patch;
+
+/// Sets up an `OpCodeMachine` with something other than the defaults.
+#[derive(Debug, Clone)]
+pub struct MachineBuilder {
+    program: Vec<i64>,
+    inputs: Vec<i64>,
+    patches: Vec<Patch>,
+    memory_model: MemoryModel,
+    budget: Budget,
+}
+
+impl MachineBuilder {
+    pub fn new(program: Vec<i64>) -> MachineBuilder {
+        MachineBuilder {
+            program,
+            inputs: Vec::new(),
+            patches: Vec::new(),
+            memory_model: MemoryModel::default(),
+            budget: Budget::default(),
+        }
+    }
+
+    pub fn memory_model(mut self, memory_model: MemoryModel) -> MachineBuilder {
+        self.memory_model = memory_model;
+        self
+    }
+
+    pub fn budget(mut self, budget: Budget) -> MachineBuilder {
+        self.budget = budget;
+        self
+    }
+
+    pub fn input(mut self, input: i64) -> MachineBuilder {
+        self.inputs.push(input);
+        self
+    }
+
+    pub fn inputs<I: IntoIterator<Item = i64>>(mut self, inputs: I) -> MachineBuilder {
+        self.inputs.extend(inputs);
+        self
+    }
+
+    /// Writes over the program before it runs. Later patches to the same
+    /// address win.
+    pub fn patch(mut self, patch: Patch) -> MachineBuilder {
+        self.patches.push(patch);
+        self
+    }
+
+    pub fn patches<I: IntoIterator<Item = Patch>>(mut self, patches: I) -> MachineBuilder {
+        self.patches.extend(patches);
+        self
+    }
+
+    pub fn build(self) -> OpCodeMachine {
+        let mut memory = Memory::with_model(self.program, self.memory_model);
+        for patch in self.patches {
+            memory.write(patch.address, patch.value);
+        }
+
+        let mut machine = OpCodeMachine::from_memory(memory).with_budget(self.budget);
+
+        for input in self.inputs {
+            machine.input(input);
+        }
+
+        machine
+    }
+}
@@ -0,0 +1,354 @@
+use crate::disassembler::{disassemble, Line};
+use crate::error::MachineError;
+use crate::machine::MachineResult;
+use crate::memory::Memory;
+use crate::op_code::*;
+use std::collections::VecDeque;
+
+/// An alternative to `OpCodeMachine` for programs where speed matters more
+/// than stepping or tracing. Each instruction in the program image is decoded
+/// once and cached, and a write to an address drops whatever was cached there,
+/// so self-modifying code still behaves. Results, including faults, are the
+/// same as `OpCodeMachine`'s.
+#[derive(Clone)]
+pub struct CachedMachine {
+    memory: Memory,
+    cache: Vec<Option<OpCode>>,
+    inputs: VecDeque<i64>,
+    pointer: usize,
+    relative_base: i64,
+    is_complete: bool,
+}
+
+impl CachedMachine {
+    pub fn new(data: Vec<i64>) -> CachedMachine {
+        let mut cache = vec![None; data.len()];
+        for line in disassemble(&data) {
+            if let Line::Instruction {
+                address, op_code, ..
+            } = line
+            {
+                cache[address] = Some(op_code);
+            }
+        }
+
+        CachedMachine {
+            memory: Memory::from(data),
+            cache,
+            inputs: VecDeque::new(),
+            pointer: 0,
+            relative_base: 0,
+            is_complete: false,
+        }
+    }
+
+    pub fn with_input(mut self, input: i64) -> CachedMachine {
+        self.inputs.push_back(input);
+        self
+    }
+
+    pub fn input(&mut self, input: i64) {
+        self.inputs.push_back(input);
+    }
+
+    pub fn pointer(&self) -> usize {
+        self.pointer
+    }
+
+    pub fn relative_base(&self) -> isize {
+        self.relative_base as isize
+    }
+
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.is_complete
+    }
+
+    /// Runs until the machine produces output, needs input, halts or faults.
+    pub fn run(&mut self) -> MachineResult {
+        let mut budget = u64::MAX;
+        self.run_budgeted(&mut budget)
+            .expect("A run can't use up u64::MAX instructions")
+    }
+
+    /// Like `run`, but executes at most `budget` instructions, taking off each
+    /// one it executes, and returns `None` if that isn't enough.
+    pub fn run_budgeted(&mut self, budget: &mut u64) -> Option<MachineResult> {
+        if self.is_complete {
+            return Some(MachineResult::Halt);
+        }
+
+        match self.execute(budget) {
+            Ok(result) => result,
+            Err(error) => Some(MachineResult::Fault(error)),
+        }
+    }
+
+    fn execute(&mut self, budget: &mut u64) -> Result<Option<MachineResult>, MachineError> {
+        loop {
+            if *budget == 0 {
+                return Ok(None);
+            }
+
+            let address = self.pointer;
+
+            self.pointer = match self.decode(address)? {
+                OpCode::Add(p1, p2, p3) => {
+                    let left = self.read(address + 1, p1)?;
+                    let right = self.read(address + 2, p2)?;
+                    let target = self.write_index(address + 3, p3)?;
+                    let value = left
+                        .checked_add(right)
+                        .ok_or(MachineError::Overflow { address })?;
+                    self.write(target, value);
+                    address + 4
+                }
+                OpCode::Multiply(p1, p2, p3) => {
+                    let left = self.read(address + 1, p1)?;
+                    let right = self.read(address + 2, p2)?;
+                    let target = self.write_index(address + 3, p3)?;
+                    let value = left
+                        .checked_mul(right)
+                        .ok_or(MachineError::Overflow { address })?;
+                    self.write(target, value);
+                    address + 4
+                }
+                OpCode::Input(p1) => {
+                    let target = self.write_index(address + 1, p1)?;
+                    match self.inputs.pop_front() {
+                        Some(value) => self.write(target, value),
+                        None => return Ok(Some(MachineResult::InputRequired)),
+                    }
+                    address + 2
+                }
+                OpCode::Output(p1) => {
+                    let value = self.read(address + 1, p1)?;
+                    self.pointer = address + 2;
+                    *budget -= 1;
+                    return Ok(Some(MachineResult::Output(value)));
+                }
+                OpCode::JumpIfTrue(p1, p2) => {
+                    if self.read(address + 1, p1)? != 0 {
+                        self.jump_target(address + 2, p2)?
+                    } else {
+                        address + 3
+                    }
+                }
+                OpCode::JumpIfFalse(p1, p2) => {
+                    if self.read(address + 1, p1)? == 0 {
+                        self.jump_target(address + 2, p2)?
+                    } else {
+                        address + 3
+                    }
+                }
+                OpCode::LessThan(p1, p2, p3) => {
+                    let value = self.read(address + 1, p1)? < self.read(address + 2, p2)?;
+                    let target = self.write_index(address + 3, p3)?;
+                    self.write(target, value as i64);
+                    address + 4
+                }
+                OpCode::Equals(p1, p2, p3) => {
+                    let value = self.read(address + 1, p1)? == self.read(address + 2, p2)?;
+                    let target = self.write_index(address + 3, p3)?;
+                    self.write(target, value as i64);
+                    address + 4
+                }
+                OpCode::AdjustRelativeBase(p1) => {
+                    self.relative_base = self
+                        .relative_base
+                        .checked_add(self.read(address + 1, p1)?)
+                        .ok_or(MachineError::Overflow { address })?;
+                    address + 2
+                }
+                OpCode::Halt => {
+                    self.is_complete = true;
+                    *budget -= 1;
+                    return Ok(Some(MachineResult::Halt));
+                }
+            };
+
+            *budget -= 1;
+        }
+    }
+
+    #[inline]
+    fn decode(&mut self, address: usize) -> Result<OpCode, MachineError> {
+        if let Some(Some(op_code)) = self.cache.get(address) {
+            return Ok(*op_code);
+        }
+
+        let value = self
+            .memory
+            .get(address)
+            .ok_or(MachineError::PointerOutOfRange(address as i64))?;
+        let op_code = get_op(value, address)?;
+
+        if let Some(entry) = self.cache.get_mut(address) {
+            *entry = Some(op_code);
+        }
+
+        Ok(op_code)
+    }
+
+    #[inline]
+    fn read(&self, location: usize, mode: ParameterMode) -> Result<i64, MachineError> {
+        let value = self.memory.read(location);
+
+        match mode {
+            ParameterMode::Position => Ok(self.memory.read(self.index(value)?)),
+            ParameterMode::Immediate => Ok(value),
+            ParameterMode::Relative => Ok(self.memory.read(self.index(self.relative(value)?)?)),
+        }
+    }
+
+    #[inline]
+    fn write_index(&self, location: usize, mode: ParameterMode) -> Result<usize, MachineError> {
+        let value = self.memory.read(location);
+
+        match mode {
+            ParameterMode::Position => self.index(value),
+            ParameterMode::Immediate => Err(MachineError::ImmediateModeWrite {
+                address: self.pointer,
+            }),
+            ParameterMode::Relative => self.index(self.relative(value)?),
+        }
+    }
+
+    #[inline]
+    fn write(&mut self, address: usize, value: i64) {
+        self.memory.write(address, value);
+
+        if let Some(entry) = self.cache.get_mut(address) {
+            *entry = None;
+        }
+    }
+
+    #[inline]
+    fn relative(&self, value: i64) -> Result<i64, MachineError> {
+        value
+            .checked_add(self.relative_base)
+            .ok_or(MachineError::Overflow {
+                address: self.pointer,
+            })
+    }
+
+    #[inline]
+    fn index(&self, target: i64) -> Result<usize, MachineError> {
+        if target < 0 {
+            return Err(MachineError::NegativeAddress {
+                address: self.pointer,
+                target,
+            });
+        }
+
+        Ok(target as usize)
+    }
+
+    #[inline]
+    fn jump_target(&self, location: usize, mode: ParameterMode) -> Result<usize, MachineError> {
+        let target = self.read(location, mode)?;
+
+        if target < 0 || target as usize >= self.memory.len() {
+            return Err(MachineError::PointerOutOfRange(target));
+        }
+
+        Ok(target as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::assemble;
+    use crate::machine::OpCodeMachine;
+
+    fn outputs(mut machine: CachedMachine) -> (Vec<i64>, MachineResult) {
+        let mut outputs = Vec::new();
+        loop {
+            match machine.run() {
+                MachineResult::Output(value) => outputs.push(value),
+                result => return (outputs, result),
+            }
+        }
+    }
+
+    fn expected(mut machine: OpCodeMachine) -> (Vec<i64>, MachineResult) {
+        let mut outputs = Vec::new();
+        loop {
+            match machine.run() {
+                MachineResult::Output(value) => outputs.push(value),
+                result => return (outputs, result),
+            }
+        }
+    }
+
+    fn assert_same(program: &[i64], inputs: &[i64]) {
+        let mut cached = CachedMachine::new(program.to_vec());
+        let mut machine = OpCodeMachine::new(program.to_vec());
+        for input in inputs {
+            cached.input(*input);
+            machine.input(*input);
+        }
+
+        assert_eq!(outputs(cached), expected(machine));
+    }
+
+    #[test]
+    fn matches_machine() {
+        let quine = [
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        assert_same(&quine, &[]);
+
+        let large_example = [
+            3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0,
+            0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4,
+            20, 1105, 1, 46, 98, 99,
+        ];
+        for input in 6..=10 {
+            assert_same(&large_example, &[input]);
+        }
+
+        assert_same(&[104, 1_125_899_906_842_624, 99], &[]);
+        assert_same(&[3, 0, 4, 0, 99], &[]);
+    }
+
+    #[test]
+    fn matches_machine_faults() {
+        assert_same(&[77], &[]);
+        assert_same(&[1101, 1, 1, 0, 99, 3], &[]);
+        assert_same(&[11101, 1, 1, 0, 99], &[]);
+        assert_same(&[4, -1, 99], &[]);
+        assert_same(&[1105, 1, -5, 99], &[]);
+        assert_same(&[104, 7], &[]);
+        assert_same(&[109, -10, 204, 0, 99], &[]);
+        assert_same(&[1102, i64::MAX, 2, 0, 99], &[]);
+        assert_same(&[109, i64::MAX, 109, 1, 99], &[]);
+    }
+
+    #[test]
+    fn self_modifying() {
+        let program = assemble(
+            "
+            start:
+                ADD  #3, #4, [out]
+                OUT  [out]
+                JT   [done], #end
+                ADD  #1, #0, [done]
+                ADD  #1102, #0, [start]
+                JT   #1, #start
+            end: HLT
+            out: data 0
+            done: data 0",
+        )
+        .unwrap();
+
+        let (values, result) = outputs(CachedMachine::new(program.clone()));
+        assert_eq!(values, vec![7, 12]);
+        assert_eq!(result, MachineResult::Halt);
+        assert_same(&program, &[]);
+    }
+}
@@ -0,0 +1,578 @@
+use crate::budget::Limit;
+use crate::disassembler::{decode, Line};
+use crate::error::MachineError;
+use crate::journal::Recording;
+use crate::machine::*;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Command {
+    Step(usize),
+    Reverse(usize),
+    Rewind(u64),
+    Next,
+    Continue(u64),
+    Break(usize),
+    Delete(usize),
+    Watch(usize),
+    Unwatch(usize),
+    Registers,
+    Memory(usize, usize),
+    Input(Vec<i64>),
+    List(usize),
+    Help,
+    Quit,
+}
+
+/// The most cells `memory` shows at once.
+pub const MAX_MEMORY_CELLS: usize = 1024;
+
+/// The most instructions `next` and `continue` run before giving control
+/// back, so a program that spins doesn't hang the debugger.
+pub const DEFAULT_STEP_LIMIT: u64 = 1_000_000;
+
+pub const HELP: &str = "\
+s, step [n]          execute the next n instructions (default 1)
+rs, reverse [n]      undo the last n instructions (default 1)
+rw, rewind <step>    go back to how things were after that many steps
+n, next              run until the instruction after this one is reached
+c, continue [n]      run until a breakpoint, watchpoint, input or halt, or
+                     until n instructions have run (default 1000000)
+b, break <addr>      stop before executing the instruction at addr
+d, delete <addr>     remove the breakpoint at addr
+w, watch <addr>      stop after the memory cell at addr is written to
+u, unwatch <addr>    remove the watchpoint on addr
+r, registers         show the pointer, relative base and queued inputs
+m, memory <addr> [n] show n memory cells from addr (default 16, at most 1024)
+i, input <values>    queue input values
+l, list [n]          disassemble n instructions from the pointer (default 8)
+h, help              show this help
+q, quit              exit the debugger";
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let mut parts = source
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|p| !p.is_empty());
+
+        let name = parts.next().ok_or("No command given")?;
+        let arguments = parts
+            .map(|p| {
+                p.parse::<i64>()
+                    .map_err(|_| format!("'{}' is not a number", p))
+            })
+            .collect::<Result<Vec<i64>, String>>()?;
+
+        let address = |index: usize| -> Result<usize, String> {
+            match arguments.get(index) {
+                Some(value) if *value >= 0 => Ok(*value as usize),
+                Some(value) => Err(format!("'{}' is not an address", value)),
+                None => Err(format!("'{}' needs an address", name)),
+            }
+        };
+        let count = |default: usize| -> Result<usize, String> {
+            match arguments.last() {
+                Some(value) if *value > 0 => Ok(*value as usize),
+                Some(value) => Err(format!("'{}' is not a count", value)),
+                None => Ok(default),
+            }
+        };
+
+        Ok(match name {
+            "s" | "step" => Command::Step(count(1)?),
+            "rs" | "reverse" => Command::Reverse(count(1)?),
+            "rw" | "rewind" => Command::Rewind(address(0)? as u64),
+            "n" | "next" => Command::Next,
+            "c" | "continue" => Command::Continue(count(DEFAULT_STEP_LIMIT as usize)? as u64),
+            "b" | "break" => Command::Break(address(0)?),
+            "d" | "delete" => Command::Delete(address(0)?),
+            "w" | "watch" => Command::Watch(address(0)?),
+            "u" | "unwatch" => Command::Unwatch(address(0)?),
+            "r" | "registers" => Command::Registers,
+            "m" | "memory" => Command::Memory(
+                address(0)?,
+                if arguments.len() > 1 { count(16)? } else { 16 },
+            ),
+            "i" | "input" if !arguments.is_empty() => Command::Input(arguments),
+            "i" | "input" => return Err("'input' needs at least one value".to_string()),
+            "l" | "list" => Command::List(count(8)?),
+            "h" | "help" => Command::Help,
+            "q" | "quit" => Command::Quit,
+            _ => return Err(format!("Unknown command '{}'", name)),
+        })
+    }
+}
+
+/// Why the debugger handed control back.
+#[derive(Debug, PartialEq)]
+pub enum Stop {
+    Stepped,
+    Breakpoint(usize),
+    Watchpoint {
+        address: usize,
+        value: i64,
+    },
+    InputRequired,
+    Halted,
+    Fault(MachineError),
+    BudgetExceeded(Limit),
+    /// Ran the most instructions it was allowed to without anything else
+    /// stopping it.
+    StepLimit(u64),
+}
+
+pub struct Debugger {
+    machine: OpCodeMachine,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: BTreeSet<usize>,
+    outputs: Vec<i64>,
+    /// The step each output was produced by.
+    output_steps: Vec<u64>,
+}
+
+impl Debugger {
+    /// Starts the machine recording, so it can be stepped backwards.
+    pub fn new(mut machine: OpCodeMachine) -> Debugger {
+        if !machine.is_recording() {
+            machine.record(Recording::default());
+        }
+
+        Debugger {
+            machine,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+            outputs: Vec::new(),
+            output_steps: Vec::new(),
+        }
+    }
+
+    pub fn machine(&self) -> &OpCodeMachine {
+        &self.machine
+    }
+
+    pub fn outputs(&self) -> &[i64] {
+        &self.outputs
+    }
+
+    /// Runs the command and returns the text to show for it.
+    pub fn execute(&mut self, command: &Command) -> String {
+        let mut log = String::new();
+
+        match command {
+            Command::Step(count) => {
+                let mut remaining = *count;
+                let stop = self.resume(&mut log, true, *count as u64, |_| {
+                    remaining -= 1;
+                    remaining == 0
+                });
+                self.describe_stop(&mut log, stop);
+            }
+            Command::Reverse(count) => {
+                let mut undone = 0;
+                while undone < *count && self.machine.step_back() {
+                    undone += 1;
+                }
+                self.forget_outputs();
+
+                if undone < *count {
+                    writeln!(log, "No more history").unwrap();
+                }
+                self.describe_position(&mut log);
+            }
+            Command::Rewind(step) => match self.machine.rewind_to(*step) {
+                Ok(()) => {
+                    self.forget_outputs();
+                    self.describe_position(&mut log);
+                }
+                Err(error) => writeln!(log, "{}", error).unwrap(),
+            },
+            Command::Next => {
+                let target = match self.current_line() {
+                    Some(Line::Instruction {
+                        address, op_code, ..
+                    }) => address + op_code.size(),
+                    _ => self.machine.pointer() + 1,
+                };
+                let stop = self.resume(&mut log, false, DEFAULT_STEP_LIMIT, |machine| {
+                    machine.pointer() == target
+                });
+                self.describe_stop(&mut log, stop);
+            }
+            Command::Continue(limit) => {
+                let stop = self.resume(&mut log, false, *limit, |_| false);
+                self.describe_stop(&mut log, stop);
+            }
+            Command::Break(address) => {
+                self.breakpoints.insert(*address);
+                writeln!(log, "Breakpoint set at {}", address).unwrap();
+            }
+            Command::Delete(address) => {
+                if self.breakpoints.remove(address) {
+                    writeln!(log, "Breakpoint at {} removed", address).unwrap();
+                } else {
+                    writeln!(log, "No breakpoint at {}", address).unwrap();
+                }
+            }
+            Command::Watch(address) => {
+                self.watchpoints.insert(*address);
+                writeln!(log, "Watching {}", address).unwrap();
+            }
+            Command::Unwatch(address) => {
+                if self.watchpoints.remove(address) {
+                    writeln!(log, "No longer watching {}", address).unwrap();
+                } else {
+                    writeln!(log, "{} isn't being watched", address).unwrap();
+                }
+            }
+            Command::Registers => {
+                writeln!(log, "step:          {}", self.machine.steps()).unwrap();
+                writeln!(log, "pointer:       {}", self.machine.pointer()).unwrap();
+                writeln!(log, "relative base: {}", self.machine.relative_base()).unwrap();
+                writeln!(log, "complete:      {}", self.machine.is_complete).unwrap();
+                writeln!(log, "inputs:        {:?}", self.machine.inputs()).unwrap();
+                writeln!(log, "outputs:       {:?}", self.outputs).unwrap();
+                writeln!(log, "breakpoints:   {:?}", self.breakpoints).unwrap();
+                writeln!(log, "watchpoints:   {:?}", self.watchpoints).unwrap();
+            }
+            Command::Memory(start, count) => {
+                let memory = self.machine.memory();
+                let end = start.saturating_add(*count.min(&MAX_MEMORY_CELLS));
+                let values: Vec<i64> = (*start..end).map(|address| memory.read(address)).collect();
+
+                for (row, chunk) in values.chunks(8).enumerate() {
+                    let cells = chunk
+                        .iter()
+                        .map(|v| format!("{:>8}", v))
+                        .collect::<String>();
+                    writeln!(log, "{:>5}:{}", start + row * 8, cells).unwrap();
+                }
+            }
+            Command::Input(values) => {
+                for value in values {
+                    self.machine.input(*value);
+                }
+                writeln!(log, "Queued inputs: {:?}", self.machine.inputs()).unwrap();
+            }
+            Command::List(count) => {
+                let memory = self.machine.memory();
+                let mut address = self.machine.pointer();
+
+                for _ in 0..*count {
+                    if address >= memory.len() {
+                        break;
+                    }
+
+                    let line = decode(&memory.read_range(address, 4), address);
+                    let marker = if address == self.machine.pointer() {
+                        "=>"
+                    } else {
+                        "  "
+                    };
+                    writeln!(log, "{}{}", marker, line).unwrap();
+
+                    address += match line {
+                        Line::Instruction { op_code, .. } => op_code.size(),
+                        Line::Data { .. } => 1,
+                    };
+                }
+            }
+            Command::Help => writeln!(log, "{}", HELP).unwrap(),
+            Command::Quit => {}
+        }
+
+        log
+    }
+
+    /// Steps the machine until `is_done` returns true after an instruction,
+    /// or until a breakpoint, watchpoint or the machine itself stops it, or
+    /// `limit` instructions have run. The instruction at the pointer is always
+    /// executed, even if it has a breakpoint, so that execution can carry on
+    /// from one.
+    pub fn resume<F>(&mut self, log: &mut String, verbose: bool, limit: u64, mut is_done: F) -> Stop
+    where
+        F: FnMut(&OpCodeMachine) -> bool,
+    {
+        let mut is_first = true;
+        let mut executed = 0;
+
+        loop {
+            if executed == limit {
+                return Stop::StepLimit(limit);
+            }
+
+            let pointer = self.machine.pointer();
+            if !is_first && self.breakpoints.contains(&pointer) {
+                return Stop::Breakpoint(pointer);
+            }
+            is_first = false;
+
+            let line = self.current_line();
+
+            let step = match self.machine.step() {
+                StepResult::Executed(step) => step,
+                StepResult::InputRequired => return Stop::InputRequired,
+                StepResult::Halted => return Stop::Halted,
+                StepResult::Fault(error) => return Stop::Fault(error),
+                StepResult::BudgetExceeded(limit) => return Stop::BudgetExceeded(limit),
+            };
+            executed += 1;
+
+            if let (true, Some(line)) = (verbose, line) {
+                writeln!(log, "  {}", line).unwrap();
+            }
+
+            match step.effect {
+                Effect::Output(value) => {
+                    self.outputs.push(value);
+                    self.output_steps.push(self.machine.steps());
+                    writeln!(log, "Output: {}", value).unwrap();
+                }
+                Effect::Write { address, value } | Effect::Input { address, value }
+                    if self.watchpoints.contains(&address) =>
+                {
+                    return Stop::Watchpoint { address, value };
+                }
+                Effect::Halt => return Stop::Halted,
+                _ => {}
+            }
+
+            if is_done(&self.machine) {
+                return Stop::Stepped;
+            }
+        }
+    }
+
+    fn current_line(&self) -> Option<Line> {
+        let memory = self.machine.memory();
+        let pointer = self.machine.pointer();
+
+        if pointer < memory.len() {
+            Some(decode(&memory.read_range(pointer, 4), pointer))
+        } else {
+            None
+        }
+    }
+
+    fn describe_stop(&self, log: &mut String, stop: Stop) {
+        match stop {
+            Stop::Stepped => {}
+            Stop::Breakpoint(address) => writeln!(log, "Breakpoint at {}", address).unwrap(),
+            Stop::Watchpoint { address, value } => {
+                writeln!(log, "Watchpoint: [{}] = {}", address, value).unwrap()
+            }
+            Stop::InputRequired => writeln!(log, "Waiting for input").unwrap(),
+            Stop::Halted => {
+                writeln!(log, "Halted").unwrap();
+                return;
+            }
+            Stop::Fault(error) => writeln!(log, "Fault: {}", error).unwrap(),
+            Stop::BudgetExceeded(limit) => writeln!(log, "{}", limit).unwrap(),
+            Stop::StepLimit(limit) => writeln!(log, "Stopped after {} steps", limit).unwrap(),
+        }
+
+        if let Some(line) = self.current_line() {
+            writeln!(log, "=>{}", line).unwrap();
+        }
+    }
+
+    fn describe_position(&self, log: &mut String) {
+        writeln!(log, "At step {}", self.machine.steps()).unwrap();
+        if let Some(line) = self.current_line() {
+            writeln!(log, "=>{}", line).unwrap();
+        }
+    }
+
+    // Drops outputs from steps that have been undone.
+    fn forget_outputs(&mut self) {
+        let step = self.machine.steps();
+        let kept = self.output_steps.iter().take_while(|&&s| s <= step).count();
+        self.outputs.truncate(kept);
+        self.output_steps.truncate(kept);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    mod command {
+        use super::super::*;
+
+        #[test]
+        fn parse() {
+            assert_eq!("s".parse(), Ok(Command::Step(1)));
+            assert_eq!("step 5".parse(), Ok(Command::Step(5)));
+            assert_eq!("b 12".parse(), Ok(Command::Break(12)));
+            assert_eq!("m 10".parse(), Ok(Command::Memory(10, 16)));
+            assert_eq!("memory 10 4".parse(), Ok(Command::Memory(10, 4)));
+            assert_eq!("i 1, 2 -3".parse(), Ok(Command::Input(vec![1, 2, -3])));
+            assert_eq!("list".parse(), Ok(Command::List(8)));
+            assert_eq!("rs".parse(), Ok(Command::Reverse(1)));
+            assert_eq!("reverse 3".parse(), Ok(Command::Reverse(3)));
+            assert_eq!("rw 40".parse(), Ok(Command::Rewind(40)));
+            assert_eq!("c".parse(), Ok(Command::Continue(DEFAULT_STEP_LIMIT)));
+            assert_eq!("continue 500".parse(), Ok(Command::Continue(500)));
+        }
+
+        #[test]
+        fn parse_errors() {
+            assert!("".parse::<Command>().is_err());
+            assert!("b".parse::<Command>().is_err());
+            assert!("b -1".parse::<Command>().is_err());
+            assert!("step x".parse::<Command>().is_err());
+            assert!("input".parse::<Command>().is_err());
+            assert!("jump 5".parse::<Command>().is_err());
+            assert!("rewind".parse::<Command>().is_err());
+        }
+    }
+
+    mod debugger {
+        use super::super::*;
+
+        // IN [counter]; loop: JF [counter], #end; OUT [counter];
+        // ADD [counter], #-1, [counter]; JT #1, #loop; end: HLT; counter: data 0
+        const COUNTDOWN: &[i64] = &[
+            3, 15, 1006, 15, 14, 4, 15, 1001, 15, -1, 15, 1105, 1, 2, 99, 0,
+        ];
+
+        fn debugger() -> Debugger {
+            Debugger::new(OpCodeMachine::new(COUNTDOWN.to_vec()))
+        }
+
+        #[test]
+        fn step_waits_for_input() {
+            let mut debugger = debugger();
+
+            let log = debugger.execute(&Command::Step(1));
+            assert!(log.starts_with("Waiting for input"));
+            assert_eq!(debugger.machine().pointer(), 0);
+
+            debugger.execute(&Command::Input(vec![2]));
+            let log = debugger.execute(&Command::Step(2));
+            assert_eq!(
+                log,
+                "      0: IN   [15]\n      2: JF   [15], #14\n=>    5: OUT  [15]\n"
+            );
+        }
+
+        #[test]
+        fn breakpoints() {
+            let mut debugger = debugger();
+            debugger.execute(&Command::Input(vec![3]));
+            debugger.execute(&Command::Break(5));
+
+            assert_eq!(
+                debugger.resume(&mut String::new(), false, DEFAULT_STEP_LIMIT, |_| false),
+                Stop::Breakpoint(5)
+            );
+            assert_eq!(
+                debugger.resume(&mut String::new(), false, DEFAULT_STEP_LIMIT, |_| false),
+                Stop::Breakpoint(5)
+            );
+            assert_eq!(debugger.outputs(), &[3]);
+
+            debugger.execute(&Command::Delete(5));
+            assert_eq!(
+                debugger.resume(&mut String::new(), false, DEFAULT_STEP_LIMIT, |_| false),
+                Stop::Halted
+            );
+            assert_eq!(debugger.outputs(), &[3, 2, 1]);
+        }
+
+        #[test]
+        fn watchpoints() {
+            let mut debugger = debugger();
+            debugger.execute(&Command::Input(vec![2]));
+            debugger.execute(&Command::Watch(15));
+
+            let log = debugger.execute(&Command::Continue(DEFAULT_STEP_LIMIT));
+            assert_eq!(log, "Watchpoint: [15] = 2\n=>    2: JF   [15], #14\n");
+
+            let log = debugger.execute(&Command::Continue(DEFAULT_STEP_LIMIT));
+            assert_eq!(
+                log,
+                "Output: 2\nWatchpoint: [15] = 1\n=>   11: JT   #1, #2\n"
+            );
+        }
+
+        #[test]
+        fn next_steps_over_loop() {
+            let mut debugger = debugger();
+            debugger.execute(&Command::Input(vec![3]));
+            debugger.execute(&Command::Break(11));
+            debugger.execute(&Command::Continue(DEFAULT_STEP_LIMIT));
+
+            debugger.execute(&Command::Delete(11));
+            let log = debugger.execute(&Command::Next);
+            assert_eq!(log, "Output: 2\nOutput: 1\n=>   14: HLT\n");
+        }
+
+        #[test]
+        fn spinning_program() {
+            // loop: JT #1, #loop
+            let mut debugger = Debugger::new(OpCodeMachine::new(vec![1105, 1, 0]));
+
+            let log = debugger.execute(&Command::Continue(100));
+            assert_eq!(log, "Stopped after 100 steps\n=>    0: JT   #1, #0\n");
+            assert_eq!(debugger.machine().steps(), 100);
+
+            // `next` from here would never reach the instruction after it.
+            assert_eq!(
+                debugger.resume(&mut String::new(), false, 1000, |machine| {
+                    machine.pointer() == 3
+                }),
+                Stop::StepLimit(1000)
+            );
+        }
+
+        #[test]
+        fn reverse() {
+            let mut debugger = debugger();
+            debugger.execute(&Command::Input(vec![3]));
+            debugger.execute(&Command::Break(11));
+            debugger.execute(&Command::Continue(DEFAULT_STEP_LIMIT));
+            debugger.execute(&Command::Continue(DEFAULT_STEP_LIMIT));
+            assert_eq!(debugger.outputs(), &[3, 2]);
+
+            let log = debugger.execute(&Command::Reverse(4));
+            assert_eq!(log, "At step 4\n=>   11: JT   #1, #2\n");
+            assert_eq!(debugger.outputs(), &[3]);
+            assert_eq!(debugger.machine().memory().read(15), 2);
+
+            let log = debugger.execute(&Command::Rewind(0));
+            assert_eq!(log, "At step 0\n=>    0: IN   [15]\n");
+            assert_eq!(debugger.outputs(), &[] as &[i64]);
+            assert_eq!(debugger.machine().inputs(), &[3]);
+
+            let log = debugger.execute(&Command::Reverse(1));
+            assert!(log.starts_with("No more history\n"));
+            let log = debugger.execute(&Command::Rewind(5));
+            assert_eq!(log, "Can't rewind forwards from step 0\n");
+        }
+
+        #[test]
+        fn registers_and_memory() {
+            let mut debugger = debugger();
+            debugger.execute(&Command::Input(vec![7, 8]));
+
+            let log = debugger.execute(&Command::Registers);
+            assert!(log.contains("pointer:       0\n"));
+            assert!(log.contains("inputs:        [7, 8]\n"));
+
+            let log = debugger.execute(&Command::Memory(12, 6));
+            assert_eq!(
+                log,
+                "   12:       1       2      99       0       0       0\n"
+            );
+
+            let log = debugger.execute(&Command::Memory(0, 99_999_999_999));
+            assert_eq!(log.lines().count(), MAX_MEMORY_CELLS / 8);
+
+            let log = debugger.execute(&Command::Memory(usize::MAX - 1, 16));
+            assert_eq!(log.lines().count(), 1);
+        }
+    }
+}
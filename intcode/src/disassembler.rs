@@ -0,0 +1,235 @@
+use crate::op_code::*;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub struct Parameter {
+    pub mode: ParameterMode,
+    pub value: i64,
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mode {
+            ParameterMode::Position => write!(f, "[{}]", self.value),
+            ParameterMode::Immediate => write!(f, "#{}", self.value),
+            ParameterMode::Relative if self.value < 0 => write!(f, "rb{}", self.value),
+            ParameterMode::Relative => write!(f, "rb+{}", self.value),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Line {
+    Instruction {
+        address: usize,
+        op_code: OpCode,
+        parameters: Vec<Parameter>,
+    },
+    Data {
+        address: usize,
+        value: i64,
+    },
+}
+
+impl Line {
+    pub fn address(&self) -> usize {
+        match self {
+            Line::Instruction { address, .. } | Line::Data { address, .. } => *address,
+        }
+    }
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Line::Instruction {
+                address,
+                op_code,
+                parameters,
+            } => {
+                if parameters.is_empty() {
+                    return write!(f, "{:>5}: {}", address, op_code.mnemonic());
+                }
+
+                let parameters = parameters
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                write!(
+                    f,
+                    "{:>5}: {:<4} {}",
+                    address,
+                    op_code.mnemonic(),
+                    parameters
+                )
+            }
+            Line::Data { address, value } => write!(f, "{:>5}: DATA {}", address, value),
+        }
+    }
+}
+
+/// Decodes the program from start to finish, one instruction after another.
+/// Words that aren't a valid op code, aren't written the way the assembler
+/// would write their op code (such as `1099`), or whose parameters would run
+/// off the end of the program come out as `Line::Data`.
+pub fn disassemble(program: &[i64]) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut address = 0;
+
+    while address < program.len() {
+        let line = disassemble_at(program, address);
+
+        address += match &line {
+            Line::Instruction { op_code, .. } => op_code.size(),
+            Line::Data { .. } => 1,
+        };
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Decodes the single instruction at `address`, which must be in the program.
+pub fn disassemble_at(program: &[i64], address: usize) -> Line {
+    decode(&program[address..], address)
+}
+
+/// Decodes the instruction at the start of `words`, labelling it with
+/// `address`. This lets a window of memory be decoded without the rest of it.
+pub fn decode(words: &[i64], address: usize) -> Line {
+    let value = words[0];
+
+    let op_code = get_op(value, address)
+        .ok()
+        .filter(|op_code| op_code.encode() == value && op_code.size() <= words.len());
+
+    match op_code {
+        Some(op_code) => {
+            let parameters = op_code
+                .parameter_modes()
+                .into_iter()
+                .enumerate()
+                .map(|(index, mode)| Parameter {
+                    mode,
+                    value: words[1 + index],
+                })
+                .collect();
+
+            Line::Instruction {
+                address,
+                op_code,
+                parameters,
+            }
+        }
+        None => Line::Data { address, value },
+    }
+}
+
+pub fn listing(program: &[i64]) -> String {
+    disassemble(program)
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_to_8_position() {
+        let program = [3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+
+        assert_eq!(
+            listing(&program),
+            "    0: IN   [9]
+    2: EQ   [9], [10], [9]
+    6: OUT  [9]
+    8: HLT
+    9: DATA -1
+   10: DATA 8
+"
+        );
+    }
+
+    #[test]
+    fn parameter_modes() {
+        let program = [21101, 5, -3, 7, 109, 19, 204, -34, 99];
+
+        assert_eq!(
+            listing(&program),
+            "    0: ADD  #5, #-3, rb+7
+    4: ARB  #19
+    6: OUT  rb-34
+    8: HLT
+"
+        );
+    }
+
+    #[test]
+    fn invalid_op_codes_are_data() {
+        let lines = disassemble(&[42, 304, 0, 99]);
+
+        assert_eq!(
+            lines,
+            vec![
+                Line::Data {
+                    address: 0,
+                    value: 42
+                },
+                Line::Data {
+                    address: 1,
+                    value: 304
+                },
+                Line::Data {
+                    address: 2,
+                    value: 0
+                },
+                Line::Instruction {
+                    address: 3,
+                    op_code: OpCode::Halt,
+                    parameters: vec![]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn non_canonical_op_codes_are_data() {
+        let lines = disassemble(&[1099, 3104, 5, 99]);
+
+        assert_eq!(
+            lines,
+            vec![
+                Line::Data {
+                    address: 0,
+                    value: 1099
+                },
+                Line::Data {
+                    address: 1,
+                    value: 3104
+                },
+                Line::Data {
+                    address: 2,
+                    value: 5
+                },
+                Line::Instruction {
+                    address: 3,
+                    op_code: OpCode::Halt,
+                    parameters: vec![]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_instruction_is_data() {
+        let lines = disassemble(&[1105, 1]);
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| matches!(line, Line::Data { .. })));
+        assert_eq!(lines[1].address(), 1);
+    }
+}
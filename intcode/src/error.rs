@@ -0,0 +1,61 @@
+use std::error::Error;
+use std::fmt;
+
+/// Reasons an `OpCodeMachine` can stop part way through an instruction.
+/// Each `address` is the location of the faulting instruction.
+#[derive(Debug, PartialEq, Clone)]
+pub enum MachineError {
+    InvalidOpCode {
+        address: usize,
+        value: i64,
+    },
+    InvalidParameterMode {
+        address: usize,
+        mode: i64,
+    },
+    ImmediateModeWrite {
+        address: usize,
+    },
+    NegativeAddress {
+        address: usize,
+        target: i64,
+    },
+    PointerOutOfRange(i64),
+    /// An addition, multiplication or relative base adjustment went past
+    /// what the machine's numbers can hold.
+    Overflow {
+        address: usize,
+    },
+    /// A value too large to use as an op code or address.
+    ValueOutOfRange {
+        address: usize,
+    },
+}
+
+impl fmt::Display for MachineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MachineError::InvalidOpCode { address, value } => {
+                write!(f, "Invalid op code {} at {}", value, address)
+            }
+            MachineError::InvalidParameterMode { address, mode } => {
+                write!(f, "Invalid parameter mode {} at {}", mode, address)
+            }
+            MachineError::ImmediateModeWrite { address } => {
+                write!(f, "Write parameter in immediate mode at {}", address)
+            }
+            MachineError::NegativeAddress { address, target } => {
+                write!(f, "Negative address {} referenced at {}", target, address)
+            }
+            MachineError::PointerOutOfRange(pointer) => {
+                write!(f, "Pointer {} is outside of memory", pointer)
+            }
+            MachineError::Overflow { address } => write!(f, "Overflow at {}", address),
+            MachineError::ValueOutOfRange { address } => {
+                write!(f, "Value out of range at {}", address)
+            }
+        }
+    }
+}
+
+impl Error for MachineError {}
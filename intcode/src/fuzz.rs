@@ -0,0 +1,491 @@
+//! Differential testing for interpreters. Random, mostly well formed programs
+//! are run on two implementations side by side, and the first place where
+//! their outputs, final state or memory disagree is reported.
+
+use crate::budget::Limit;
+use crate::cached::CachedMachine;
+use crate::disassembler::listing;
+use crate::error::MachineError;
+use crate::machine::{MachineResult, OpCodeMachine};
+use crate::memory::Memory;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+/// An interpreter that can be fuzzed against another one.
+pub trait Interpreter {
+    fn load(program: Vec<i64>) -> Self;
+
+    fn input(&mut self, value: i64);
+
+    /// Runs like `OpCodeMachine::run`, but executes at most `budget`
+    /// instructions, taking off each one it executes. Returns `None` if the
+    /// budget runs out first.
+    fn run_budgeted(&mut self, budget: &mut u64) -> Option<MachineResult>;
+
+    fn memory(&self) -> &Memory;
+
+    fn pointer(&self) -> usize;
+}
+
+impl Interpreter for OpCodeMachine {
+    fn load(program: Vec<i64>) -> OpCodeMachine {
+        OpCodeMachine::new(program)
+    }
+
+    fn input(&mut self, value: i64) {
+        OpCodeMachine::input(self, value)
+    }
+
+    fn run_budgeted(&mut self, budget: &mut u64) -> Option<MachineResult> {
+        let steps = self.steps();
+        let previous = self.budget();
+        self.set_budget(previous.steps_per_run(*budget));
+
+        let result = self.run();
+        self.set_budget(previous);
+        *budget -= self.steps() - steps;
+
+        match result {
+            MachineResult::BudgetExceeded(Limit::StepsPerRun) => None,
+            result => Some(result),
+        }
+    }
+
+    fn memory(&self) -> &Memory {
+        OpCodeMachine::memory(self)
+    }
+
+    fn pointer(&self) -> usize {
+        OpCodeMachine::pointer(self)
+    }
+}
+
+impl Interpreter for CachedMachine {
+    fn load(program: Vec<i64>) -> CachedMachine {
+        CachedMachine::new(program)
+    }
+
+    fn input(&mut self, value: i64) {
+        CachedMachine::input(self, value)
+    }
+
+    fn run_budgeted(&mut self, budget: &mut u64) -> Option<MachineResult> {
+        CachedMachine::run_budgeted(self, budget)
+    }
+
+    fn memory(&self) -> &Memory {
+        CachedMachine::memory(self)
+    }
+
+    fn pointer(&self) -> usize {
+        CachedMachine::pointer(self)
+    }
+}
+
+/// A small SplitMix64 generator, so a seed gives the same programs on every
+/// platform and with every version of the crate's dependencies.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    /// A number in `low..high`.
+    pub fn range(&mut self, low: i64, high: i64) -> i64 {
+        low + self.below((high - low) as u64) as i64
+    }
+
+    /// True roughly once in every `n` calls.
+    pub fn one_in(&mut self, n: u64) -> bool {
+        self.below(n) == 0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FuzzConfig {
+    pub seed: u64,
+    pub cases: usize,
+    /// The length of each generated program.
+    pub program_len: usize,
+    /// The most inputs queued for each program.
+    pub max_inputs: usize,
+    /// How many instructions each interpreter may execute per program.
+    pub step_budget: u64,
+}
+
+impl Default for FuzzConfig {
+    fn default() -> FuzzConfig {
+        FuzzConfig {
+            seed: 2019,
+            cases: 500,
+            program_len: 40,
+            max_inputs: 4,
+            step_budget: 10_000,
+        }
+    }
+}
+
+/// A generated program and the inputs it's given.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Case {
+    /// The seed that regenerates this case with `Case::generate`.
+    pub seed: u64,
+    pub program: Vec<i64>,
+    pub inputs: Vec<i64>,
+}
+
+impl Case {
+    pub fn generate(seed: u64, config: &FuzzConfig) -> Case {
+        let mut rng = Rng::new(seed);
+        let len = config.program_len.max(1);
+
+        let mut program = Vec::with_capacity(len + 3);
+        while program.len() < len {
+            if rng.one_in(12) {
+                program.push(rng.range(-5, 100));
+            } else {
+                push_instruction(&mut rng, &mut program, len);
+            }
+        }
+
+        let inputs = (0..rng.below(config.max_inputs as u64 + 1))
+            .map(|_| rng.range(-10, 10))
+            .collect();
+
+        Case {
+            seed,
+            program,
+            inputs,
+        }
+    }
+}
+
+// Op codes with the number of parameters each takes, and which of those (if
+// any) is written to.
+const OP_CODES: [(i64, usize, Option<usize>); 10] = [
+    (1, 3, Some(2)),
+    (2, 3, Some(2)),
+    (3, 1, Some(0)),
+    (4, 1, None),
+    (5, 2, None),
+    (6, 2, None),
+    (7, 3, Some(2)),
+    (8, 3, Some(2)),
+    (9, 1, None),
+    (99, 0, None),
+];
+
+fn push_instruction(rng: &mut Rng, program: &mut Vec<i64>, len: usize) {
+    let (code, count, written) = OP_CODES[rng.below(OP_CODES.len() as u64) as usize];
+    let is_jump = code == 5 || code == 6;
+
+    let mut instruction = code;
+    let mut parameters = Vec::with_capacity(count);
+    for index in 0..count {
+        let mode = match rng.below(20) {
+            0 => 3,
+            1 if Some(index) == written => 1,
+            _ if Some(index) == written => [0, 2][rng.below(2) as usize],
+            n => [0, 0, 1, 1, 2][n as usize % 5],
+        };
+        instruction += mode * 10_i64.pow(index as u32 + 2);
+
+        let is_target = is_jump && index == 1;
+        parameters.push(match mode {
+            1 if is_target => rng.range(-1, len as i64 + 2),
+            1 if code == 9 => rng.range(-5, 10),
+            1 => rng.range(-5, 20),
+            2 => rng.range(-4, len as i64),
+            _ => rng.range(0, len as i64 + 8),
+        });
+    }
+
+    program.push(instruction);
+    program.extend(parameters);
+}
+
+/// How a run ended.
+#[derive(Debug, Clone, PartialEq)]
+pub enum End {
+    Halt,
+    InputRequired,
+    Fault(MachineError),
+    OutOfSteps,
+    BudgetExceeded(Limit),
+    /// The interpreter panicked, which is always a bug in it.
+    Panic,
+}
+
+/// Everything observable about a finished run.
+#[derive(Debug, Clone)]
+pub struct Run {
+    pub outputs: Vec<i64>,
+    pub end: End,
+    pub pointer: usize,
+    pub memory: Memory,
+}
+
+/// Runs `case` on an interpreter until it stops or uses up `budget`.
+pub fn run<I: Interpreter>(case: &Case, budget: u64) -> Run {
+    let mut interpreter = I::load(case.program.clone());
+    for input in &case.inputs {
+        interpreter.input(*input);
+    }
+
+    let mut outputs = Vec::new();
+    let mut remaining = budget;
+    let end = loop {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            interpreter.run_budgeted(&mut remaining)
+        }));
+
+        match result {
+            Ok(Some(MachineResult::Output(value))) => outputs.push(value),
+            Ok(Some(MachineResult::Halt)) => break End::Halt,
+            Ok(Some(MachineResult::InputRequired)) => break End::InputRequired,
+            Ok(Some(MachineResult::Fault(error))) => break End::Fault(error),
+            Ok(Some(MachineResult::BudgetExceeded(limit))) => break End::BudgetExceeded(limit),
+            Ok(None) => break End::OutOfSteps,
+            Err(_) => break End::Panic,
+        }
+    };
+
+    Run {
+        outputs,
+        end,
+        pointer: interpreter.pointer(),
+        memory: interpreter.memory().clone(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    /// The runs produced different outputs; `index` is the first that differs.
+    Output {
+        index: usize,
+        left: Option<i64>,
+        right: Option<i64>,
+    },
+    End {
+        left: End,
+        right: End,
+    },
+    Pointer {
+        left: usize,
+        right: usize,
+    },
+    Memory {
+        address: usize,
+        left: i64,
+        right: i64,
+    },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difference::Output { index, left, right } => {
+                write!(f, "Output {} was {:?} and {:?}", index, left, right)
+            }
+            Difference::End { left, right } => write!(f, "Ended with {:?} and {:?}", left, right),
+            Difference::Pointer { left, right } => {
+                write!(f, "Stopped at {} and {}", left, right)
+            }
+            Difference::Memory {
+                address,
+                left,
+                right,
+            } => write!(f, "Address {} held {} and {}", address, left, right),
+        }
+    }
+}
+
+/// A case the two interpreters disagree on.
+#[derive(Debug, Clone)]
+pub struct Divergence {
+    pub case: Case,
+    pub difference: Difference,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.difference)?;
+        writeln!(f, "Seed: {}", self.case.seed)?;
+        writeln!(f, "Inputs: {:?}", self.case.inputs)?;
+        writeln!(f, "Program: {:?}", self.case.program)?;
+        write!(f, "{}", listing(&self.case.program))
+    }
+}
+
+/// Compares two runs, outputs first, then how they ended, then where they
+/// stopped and finally memory.
+pub fn compare(left: &Run, right: &Run) -> Option<Difference> {
+    let outputs = left.outputs.len().max(right.outputs.len());
+    if let Some(index) = (0..outputs).find(|&i| left.outputs.get(i) != right.outputs.get(i)) {
+        return Some(Difference::Output {
+            index,
+            left: left.outputs.get(index).copied(),
+            right: right.outputs.get(index).copied(),
+        });
+    }
+
+    if left.end != right.end {
+        return Some(Difference::End {
+            left: left.end.clone(),
+            right: right.end.clone(),
+        });
+    }
+
+    // Where a panic left things depends on where the interpreter checks for
+    // overflow, so there's nothing more to compare.
+    if left.end == End::Panic {
+        return None;
+    }
+
+    if left.pointer != right.pointer {
+        return Some(Difference::Pointer {
+            left: left.pointer,
+            right: right.pointer,
+        });
+    }
+
+    let len = left.memory.len().max(right.memory.len());
+    (0..len)
+        .find(|&address| left.memory.read(address) != right.memory.read(address))
+        .map(|address| Difference::Memory {
+            address,
+            left: left.memory.read(address),
+            right: right.memory.read(address),
+        })
+}
+
+/// Runs `config.cases` generated programs on both interpreters and returns
+/// the first divergence, or the number of cases run if there isn't one.
+pub fn differential<A: Interpreter, B: Interpreter>(
+    config: &FuzzConfig,
+) -> Result<usize, Divergence> {
+    let mut seeds = Rng::new(config.seed);
+
+    for _ in 0..config.cases {
+        let case = Case::generate(seeds.next_u64(), config);
+        let left = run::<A>(&case, config.step_budget);
+        let right = run::<B>(&case, config.step_budget);
+
+        if let Some(difference) = compare(&left, &right) {
+            return Err(Divergence { case, difference });
+        }
+    }
+
+    Ok(config.cases)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budget::Budget;
+
+    #[test]
+    fn machine_matches_cached() {
+        if let Err(divergence) =
+            differential::<OpCodeMachine, CachedMachine>(&FuzzConfig::default())
+        {
+            panic!("{}", divergence);
+        }
+    }
+
+    #[test]
+    fn generated_programs_do_things() {
+        let config = FuzzConfig::default();
+        let mut seeds = Rng::new(config.seed);
+        let runs = (0..config.cases)
+            .map(|_| run::<OpCodeMachine>(&Case::generate(seeds.next_u64(), &config), 10_000))
+            .collect::<Vec<Run>>();
+
+        // The generator is only useful if it gets past the first instruction
+        // often enough to cover every way of stopping.
+        assert!(runs.iter().any(|run| run.end == End::Halt));
+        assert!(runs.iter().any(|run| run.end == End::InputRequired));
+        assert!(runs.iter().any(|run| run.end == End::OutOfSteps));
+        assert!(runs.iter().any(|run| matches!(run.end, End::Fault(_))));
+        assert!(runs.iter().filter(|run| !run.outputs.is_empty()).count() > 20);
+    }
+
+    #[test]
+    fn same_seed_same_case() {
+        let config = FuzzConfig::default();
+        assert_eq!(Case::generate(7, &config), Case::generate(7, &config));
+        assert_ne!(Case::generate(7, &config), Case::generate(8, &config));
+    }
+
+    #[test]
+    fn run_budgeted_keeps_budget() {
+        let budget = Budget::default().steps_per_run(7).total_steps(50);
+        let mut machine = OpCodeMachine::new(vec![1105, 1, 0]).with_budget(budget);
+
+        let mut remaining = 3;
+        assert_eq!(machine.run_budgeted(&mut remaining), None);
+        assert_eq!(remaining, 0);
+        assert_eq!(machine.budget(), budget);
+    }
+
+    // Drops writes past the end of the program, like the old day 7
+    // interpreter did.
+    struct FixedSize(OpCodeMachine, usize);
+
+    impl Interpreter for FixedSize {
+        fn load(program: Vec<i64>) -> FixedSize {
+            let len = program.len();
+            FixedSize(OpCodeMachine::new(program), len)
+        }
+
+        fn input(&mut self, value: i64) {
+            self.0.input(value)
+        }
+
+        fn run_budgeted(&mut self, budget: &mut u64) -> Option<MachineResult> {
+            let result = self.0.run_budgeted(budget);
+
+            let mut snapshot = self.0.snapshot();
+            for value in snapshot.memory.iter_mut().skip(self.1) {
+                *value = 0;
+            }
+            snapshot.sparse.clear();
+            self.0 = OpCodeMachine::from_snapshot(snapshot).unwrap();
+
+            result
+        }
+
+        fn memory(&self) -> &Memory {
+            self.0.memory()
+        }
+
+        fn pointer(&self) -> usize {
+            self.0.pointer()
+        }
+    }
+
+    #[test]
+    fn finds_divergence() {
+        let divergence = differential::<OpCodeMachine, FixedSize>(&FuzzConfig::default())
+            .expect_err("Dropping writes past the end should be noticed");
+
+        let replay = Case::generate(divergence.case.seed, &FuzzConfig::default());
+        assert_eq!(replay, divergence.case);
+        assert!(divergence.to_string().contains("Seed: "));
+    }
+}
@@ -0,0 +1,390 @@
+use crate::machine::Step;
+use crate::op_code::OpKind;
+use std::ops::Range;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Something a hook has been told about. Each `pointer` is the address of the
+/// instruction responsible.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Event<N = i64> {
+    /// An instruction of a watched kind has executed.
+    Executed(Step<N>),
+    /// A parameter was read from a watched address. Reads are reported as the
+    /// instruction makes them, even if it then faults.
+    Read {
+        pointer: usize,
+        address: usize,
+        value: N,
+    },
+    /// A watched address is about to be written to, by an instruction or an
+    /// input. `value` is what will be written, after any earlier hook's
+    /// override.
+    Write {
+        pointer: usize,
+        address: usize,
+        old: N,
+        value: N,
+    },
+    RelativeBase {
+        pointer: usize,
+        old: isize,
+        new: isize,
+    },
+}
+
+/// Identifies a hook so it can be removed.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct HookId(usize);
+
+type Hook<N> = Arc<Mutex<dyn FnMut(&Event<N>) + Send>>;
+type WriteHook<N> = Arc<Mutex<dyn FnMut(&Event<N>) -> Option<N> + Send>>;
+
+enum Watch<N> {
+    Op(OpKind, Hook<N>),
+    Read(Range<usize>, Hook<N>),
+    Write(Range<usize>, WriteHook<N>),
+    RelativeBase(Hook<N>),
+}
+
+impl<N> Clone for Watch<N> {
+    fn clone(&self) -> Watch<N> {
+        match self {
+            Watch::Op(kind, hook) => Watch::Op(*kind, hook.clone()),
+            Watch::Read(addresses, hook) => Watch::Read(addresses.clone(), hook.clone()),
+            Watch::Write(addresses, hook) => Watch::Write(addresses.clone(), hook.clone()),
+            Watch::RelativeBase(hook) => Watch::RelativeBase(hook.clone()),
+        }
+    }
+}
+
+/// The hooks an `OpCodeMachine` calls as it runs, for instrumenting a program
+/// without changing the interpreter. Hooks are called in the order they were
+/// added. A cloned machine shares its hooks with the original.
+pub struct Hooks<N = i64> {
+    next: usize,
+    watches: Vec<(HookId, Watch<N>)>,
+}
+
+impl<N> Clone for Hooks<N> {
+    fn clone(&self) -> Hooks<N> {
+        Hooks {
+            next: self.next,
+            watches: self.watches.clone(),
+        }
+    }
+}
+
+impl<N> Default for Hooks<N> {
+    fn default() -> Hooks<N> {
+        Hooks {
+            next: 0,
+            watches: Vec::new(),
+        }
+    }
+}
+
+impl<N: Clone> Hooks<N> {
+    pub fn is_empty(&self) -> bool {
+        self.watches.is_empty()
+    }
+
+    /// Calls `hook` with an `Event::Executed` after each instruction of `kind`.
+    pub fn on_op<F>(&mut self, kind: OpKind, hook: F) -> HookId
+    where
+        F: FnMut(&Event<N>) + Send + 'static,
+    {
+        self.add(Watch::Op(kind, Arc::new(Mutex::new(hook))))
+    }
+
+    /// Calls `hook` with an `Event::Read` for each parameter read from an
+    /// address in `addresses`.
+    pub fn on_read<F>(&mut self, addresses: Range<usize>, hook: F) -> HookId
+    where
+        F: FnMut(&Event<N>) + Send + 'static,
+    {
+        self.add(Watch::Read(addresses, Arc::new(Mutex::new(hook))))
+    }
+
+    /// Calls `hook` with an `Event::Write` before each write to an address in
+    /// `addresses`. Returning a value writes that instead. The `Step` for an
+    /// input still shows the value that was input.
+    pub fn on_write<F>(&mut self, addresses: Range<usize>, hook: F) -> HookId
+    where
+        F: FnMut(&Event<N>) -> Option<N> + Send + 'static,
+    {
+        self.add(Watch::Write(addresses, Arc::new(Mutex::new(hook))))
+    }
+
+    /// Calls `hook` with an `Event::RelativeBase` each time it's adjusted.
+    pub fn on_relative_base<F>(&mut self, hook: F) -> HookId
+    where
+        F: FnMut(&Event<N>) + Send + 'static,
+    {
+        self.add(Watch::RelativeBase(Arc::new(Mutex::new(hook))))
+    }
+
+    /// Returns whether there was a hook to remove.
+    pub fn remove(&mut self, id: HookId) -> bool {
+        let len = self.watches.len();
+        self.watches.retain(|(watch_id, _)| *watch_id != id);
+        self.watches.len() != len
+    }
+
+    pub fn clear(&mut self) {
+        self.watches.clear();
+    }
+
+    fn add(&mut self, watch: Watch<N>) -> HookId {
+        let id = HookId(self.next);
+        self.next += 1;
+        self.watches.push((id, watch));
+        id
+    }
+
+    pub(crate) fn executed(&self, step: &Step<N>) {
+        let kind = step.op_code.kind();
+        for (_, watch) in &self.watches {
+            if let Watch::Op(watched, hook) = watch {
+                if *watched == kind {
+                    call(hook, &Event::Executed(step.clone()));
+                }
+            }
+        }
+    }
+
+    pub(crate) fn read(&self, pointer: usize, address: usize, value: &N) {
+        for (_, watch) in &self.watches {
+            if let Watch::Read(addresses, hook) = watch {
+                if addresses.contains(&address) {
+                    call(
+                        hook,
+                        &Event::Read {
+                            pointer,
+                            address,
+                            value: value.clone(),
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    /// Returns the value to write.
+    pub(crate) fn write(&self, pointer: usize, address: usize, old: &N, mut value: N) -> N {
+        for (_, watch) in &self.watches {
+            if let Watch::Write(addresses, hook) = watch {
+                if addresses.contains(&address) {
+                    let event = Event::Write {
+                        pointer,
+                        address,
+                        old: old.clone(),
+                        value: value.clone(),
+                    };
+                    let mut hook = hook.lock().unwrap_or_else(PoisonError::into_inner);
+                    if let Some(replacement) = hook(&event) {
+                        value = replacement;
+                    }
+                }
+            }
+        }
+
+        value
+    }
+
+    pub(crate) fn relative_base(&self, pointer: usize, old: isize, new: isize) {
+        for (_, watch) in &self.watches {
+            if let Watch::RelativeBase(hook) = watch {
+                call(hook, &Event::RelativeBase { pointer, old, new });
+            }
+        }
+    }
+}
+
+fn call<N>(hook: &Hook<N>, event: &Event<N>) {
+    let mut hook = hook.lock().unwrap_or_else(PoisonError::into_inner);
+    hook(event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::assemble;
+    use crate::machine::{Effect, MachineResult, OpCodeMachine};
+    use std::sync::mpsc::channel;
+
+    // Counts down from its input, outputting the square of each number from
+    // a table indexed with the relative base.
+    fn countdown() -> OpCodeMachine {
+        let program = assemble(
+            "
+                IN   [n]
+            loop:
+                ARB  [n]
+                OUT  rb+squares
+                MUL  [n], #-1, [negative]
+                ARB  [negative]
+                ADD  [n], #-1, [n]
+                JT   [n], #loop
+                HLT
+            n:  data 0
+            negative: data 0
+            squares: data 0, 1, 4, 9, 16",
+        )
+        .unwrap();
+
+        OpCodeMachine::new(program)
+    }
+
+    fn outputs(machine: &mut OpCodeMachine) -> (Vec<i64>, MachineResult) {
+        let mut outputs = Vec::new();
+        loop {
+            match machine.run() {
+                MachineResult::Output(value) => outputs.push(value),
+                result => return (outputs, result),
+            }
+        }
+    }
+
+    #[test]
+    fn outputs_and_writes() {
+        let mut machine = countdown().with_input(3);
+        let n = machine.memory().len() - 7;
+        let (sender, receiver) = channel();
+
+        let output = sender.clone();
+        machine
+            .hooks_mut()
+            .on_op(OpKind::Output, move |event| output.send(*event).unwrap());
+        machine.hooks_mut().on_write(n..n + 1, move |event| {
+            sender.send(*event).unwrap();
+            None
+        });
+
+        assert_eq!(outputs(&mut machine), (vec![9, 4, 1], MachineResult::Halt));
+
+        let events = receiver.try_iter().collect::<Vec<Event>>();
+        assert_eq!(events.len(), 7);
+        assert_eq!(
+            events[0],
+            Event::Write {
+                pointer: 0,
+                address: n,
+                old: 0,
+                value: 3
+            }
+        );
+        match events[1] {
+            Event::Executed(step) => assert_eq!(step.effect, Effect::Output(9)),
+            event => panic!("Unexpected {:?}", event),
+        }
+        assert_eq!(
+            events[6],
+            Event::Write {
+                pointer: 12,
+                address: n,
+                old: 1,
+                value: 0
+            }
+        );
+    }
+
+    #[test]
+    fn override_write() {
+        let mut machine = countdown().with_input(4);
+        let n = machine.memory().len() - 7;
+
+        // Skip 3 and 1.
+        machine.hooks_mut().on_write(n..n + 1, |event| match event {
+            Event::Write { value, .. } if value % 2 == 1 => Some(value - 1),
+            _ => None,
+        });
+
+        assert_eq!(outputs(&mut machine), (vec![16, 4], MachineResult::Halt));
+    }
+
+    #[test]
+    fn reads() {
+        let mut machine = countdown().with_input(2);
+        let squares = machine.memory().len() - 5;
+        let (sender, receiver) = channel();
+
+        machine
+            .hooks_mut()
+            .on_read(squares..squares + 5, move |event| {
+                sender.send(*event).unwrap()
+            });
+
+        assert_eq!(outputs(&mut machine), (vec![4, 1], MachineResult::Halt));
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<Event>>(),
+            vec![
+                Event::Read {
+                    pointer: 4,
+                    address: squares + 2,
+                    value: 4
+                },
+                Event::Read {
+                    pointer: 4,
+                    address: squares + 1,
+                    value: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn relative_base() {
+        let mut machine = countdown().with_input(2);
+        let (sender, receiver) = channel();
+
+        machine
+            .hooks_mut()
+            .on_relative_base(move |event| sender.send(*event).unwrap());
+
+        outputs(&mut machine);
+
+        let bases = receiver
+            .try_iter()
+            .map(|event| match event {
+                Event::RelativeBase { old, new, .. } => (old, new),
+                event => panic!("Unexpected {:?}", event),
+            })
+            .collect::<Vec<(isize, isize)>>();
+        assert_eq!(bases, vec![(0, 2), (2, 0), (0, 1), (1, 0)]);
+    }
+
+    #[test]
+    fn remove() {
+        let mut machine = countdown().with_input(2);
+        let (sender, receiver) = channel();
+
+        let id = machine
+            .hooks_mut()
+            .on_op(OpKind::Output, move |event| sender.send(*event).unwrap());
+
+        assert_eq!(machine.run(), MachineResult::Output(4));
+        assert!(machine.hooks_mut().remove(id));
+        assert!(!machine.hooks_mut().remove(id));
+        assert_eq!(machine.run(), MachineResult::Output(1));
+
+        assert_eq!(receiver.try_iter().count(), 1);
+        assert!(machine.hooks().is_empty());
+    }
+
+    #[test]
+    fn clones_share_hooks() {
+        let mut machine = countdown();
+        let (sender, receiver) = channel();
+
+        machine
+            .hooks_mut()
+            .on_op(OpKind::Halt, move |event| sender.send(*event).unwrap());
+
+        let mut copy = machine.clone().with_input(1);
+        machine.input(2);
+
+        outputs(&mut machine);
+        outputs(&mut copy);
+        assert_eq!(receiver.try_iter().count(), 2);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::machine::{MachineResult, OpCodeMachine};
+use crate::trace::Tracer;
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+/// Somewhere for a machine to get its input from and send its output to.
+pub trait MachineIo {
+    /// The next input, or `None` if there isn't one.
+    fn read(&mut self) -> Option<i64>;
+
+    fn write(&mut self, value: i64);
+}
+
+/// Reads from and writes to channels, so machines on different threads can be
+/// wired together. Reading blocks until a value arrives, and gives up when
+/// every sender has gone. Output sent after the receiver has gone is dropped.
+#[derive(Debug)]
+pub struct ChannelIo {
+    input: Receiver<i64>,
+    output: Sender<i64>,
+}
+
+impl ChannelIo {
+    pub fn new(input: Receiver<i64>, output: Sender<i64>) -> ChannelIo {
+        ChannelIo { input, output }
+    }
+}
+
+impl MachineIo for ChannelIo {
+    fn read(&mut self) -> Option<i64> {
+        self.input.recv().ok()
+    }
+
+    fn write(&mut self, value: i64) {
+        let _ = self.output.send(value);
+    }
+}
+
+/// Takes input from an iterator and collects the output.
+#[derive(Debug, Clone)]
+pub struct IterIo<I> {
+    inputs: I,
+    outputs: Vec<i64>,
+}
+
+impl<I: Iterator<Item = i64>> IterIo<I> {
+    pub fn new<V: IntoIterator<IntoIter = I>>(inputs: V) -> IterIo<I> {
+        IterIo {
+            inputs: inputs.into_iter(),
+            outputs: Vec::new(),
+        }
+    }
+
+    pub fn outputs(&self) -> &[i64] {
+        &self.outputs
+    }
+
+    pub fn into_outputs(self) -> Vec<i64> {
+        self.outputs
+    }
+}
+
+impl<I: Iterator<Item = i64>> MachineIo for IterIo<I> {
+    fn read(&mut self) -> Option<i64> {
+        self.inputs.next()
+    }
+
+    fn write(&mut self, value: i64) {
+        self.outputs.push(value);
+    }
+}
+
+/// Calls one closure for each input and another with each output.
+pub struct FnIo<R, W> {
+    read: R,
+    write: W,
+}
+
+impl<R: FnMut() -> Option<i64>, W: FnMut(i64)> FnIo<R, W> {
+    pub fn new(read: R, write: W) -> FnIo<R, W> {
+        FnIo { read, write }
+    }
+}
+
+impl<R: FnMut() -> Option<i64>, W: FnMut(i64)> MachineIo for FnIo<R, W> {
+    fn read(&mut self) -> Option<i64> {
+        (self.read)()
+    }
+
+    fn write(&mut self, value: i64) {
+        (self.write)(value)
+    }
+}
+
+impl<T: Tracer> OpCodeMachine<T> {
+    /// Runs until the machine halts, faults or `io` runs out of input, which
+    /// returns `MachineResult::InputRequired`. Queued inputs are used first.
+    pub fn run_with<I: MachineIo + ?Sized>(&mut self, io: &mut I) -> MachineResult {
+        loop {
+            match self.run() {
+                MachineResult::Output(value) => io.write(value),
+                MachineResult::InputRequired => match io.read() {
+                    Some(value) => self.input(value),
+                    None => return MachineResult::InputRequired,
+                },
+                result => return result,
+            }
+        }
+    }
+
+    /// Runs the machine on its own thread, reading from `input` and writing to
+    /// `output`. The thread ends, dropping `output`, when the machine stops.
+    pub fn spawn(mut self, input: Receiver<i64>, output: Sender<i64>) -> JoinHandle<MachineResult>
+    where
+        T: Send + 'static,
+    {
+        thread::spawn(move || self.run_with(&mut ChannelIo::new(input, output)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    // Doubles each input until it's given 0.
+    const DOUBLER: [i64; 16] = [
+        3, 15, 1006, 15, 14, 1002, 15, 2, 15, 4, 15, 1105, 1, 0, 99, 0,
+    ];
+
+    fn doubler() -> OpCodeMachine {
+        OpCodeMachine::new(DOUBLER.to_vec())
+    }
+
+    #[test]
+    fn iterator() {
+        let mut io = IterIo::new(vec![1, 2, 3, 0]);
+
+        assert_eq!(doubler().run_with(&mut io), MachineResult::Halt);
+        assert_eq!(io.outputs(), &[2, 4, 6]);
+    }
+
+    #[test]
+    fn iterator_runs_out() {
+        let mut io = IterIo::new(vec![5]);
+
+        assert_eq!(doubler().run_with(&mut io), MachineResult::InputRequired);
+        assert_eq!(io.into_outputs(), vec![10]);
+    }
+
+    #[test]
+    fn closures() {
+        let mut inputs = vec![0, 7];
+        let mut outputs = Vec::new();
+        let mut io = FnIo::new(|| inputs.pop(), |value| outputs.push(value));
+
+        assert_eq!(doubler().run_with(&mut io), MachineResult::Halt);
+        assert_eq!(outputs, vec![14]);
+    }
+
+    #[test]
+    fn channels() {
+        let (input, machine_input) = channel();
+        let (machine_output, output) = channel();
+        let handle = doubler().spawn(machine_input, machine_output);
+
+        input.send(21).unwrap();
+        assert_eq!(output.recv(), Ok(42));
+        input.send(0).unwrap();
+
+        assert_eq!(handle.join().unwrap(), MachineResult::Halt);
+        assert!(output.recv().is_err());
+    }
+
+    #[test]
+    fn feedback_loop() {
+        // The second amplifier example from day 7, with each amplifier on its
+        // own thread.
+        let program = vec![
+            3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1,
+            28, 1005, 28, 6, 99, 0, 0, 5,
+        ];
+
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..5).map(|_| channel()).unzip();
+        let (last_output, host) = channel();
+
+        for (phase, sender) in [9, 8, 7, 6, 5].iter().zip(&senders) {
+            sender.send(*phase).unwrap();
+        }
+        senders[0].send(0).unwrap();
+
+        let handles = receivers
+            .into_iter()
+            .enumerate()
+            .map(|(index, receiver)| {
+                let output = match index {
+                    4 => last_output.clone(),
+                    _ => senders[index + 1].clone(),
+                };
+                OpCodeMachine::new(program.clone()).spawn(receiver, output)
+            })
+            .collect::<Vec<_>>();
+        drop(last_output);
+
+        let mut thrust = 0;
+        for value in host {
+            thrust = value;
+            let _ = senders[0].send(value);
+        }
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), MachineResult::Halt);
+        }
+        assert_eq!(thrust, 139_629_729);
+    }
+}
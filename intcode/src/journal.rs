@@ -0,0 +1,164 @@
+use crate::memory::Memory;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 10_000;
+pub const DEFAULT_CHECKPOINTS: usize = 10;
+
+/// How much history a recording `OpCodeMachine` keeps. A checkpoint of the
+/// whole machine is taken every `interval` steps, and only the last
+/// `checkpoints` of them are kept, along with the undo journal back to the
+/// oldest one. Rewinding a long way restores a checkpoint instead of undoing
+/// every step.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Recording {
+    pub interval: u64,
+    pub checkpoints: usize,
+}
+
+impl Default for Recording {
+    fn default() -> Recording {
+        Recording {
+            interval: DEFAULT_CHECKPOINT_INTERVAL,
+            checkpoints: DEFAULT_CHECKPOINTS,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum RewindError {
+    NotRecording,
+    /// The history before `earliest` has been dropped.
+    TooFarBack {
+        earliest: u64,
+    },
+    /// The machine hasn't got to that step yet.
+    InFuture {
+        current: u64,
+    },
+}
+
+impl fmt::Display for RewindError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RewindError::NotRecording => write!(f, "The machine isn't recording"),
+            RewindError::TooFarBack { earliest } => {
+                write!(f, "Can't rewind past step {}", earliest)
+            }
+            RewindError::InFuture { current } => {
+                write!(f, "Can't rewind forwards from step {}", current)
+            }
+        }
+    }
+}
+
+impl Error for RewindError {}
+
+/// What's needed to undo one instruction.
+#[derive(Debug, Clone)]
+pub(crate) struct Entry<N> {
+    pub pointer: usize,
+    pub relative_base: isize,
+    /// The length of memory before the instruction.
+    pub len: usize,
+    /// The address written to and the value it held.
+    pub write: Option<(usize, N)>,
+    pub input: Option<N>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Checkpoint<N> {
+    pub step: u64,
+    pub data: Memory<N>,
+    pub pointer: usize,
+    pub relative_base: isize,
+    pub is_complete: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Journal<N> {
+    recording: Recording,
+    origin: u64,
+    /// The step the first entry undoes back to.
+    start: u64,
+    entries: VecDeque<Entry<N>>,
+    checkpoints: VecDeque<Checkpoint<N>>,
+    /// The write made by the instruction being executed.
+    pub write: Option<(usize, N)>,
+}
+
+impl<N> Journal<N> {
+    pub fn new(recording: Recording, checkpoint: Checkpoint<N>) -> Journal<N> {
+        let mut checkpoints = VecDeque::new();
+        let step = checkpoint.step;
+        checkpoints.push_back(checkpoint);
+
+        Journal {
+            recording,
+            origin: step,
+            start: step,
+            entries: VecDeque::new(),
+            checkpoints,
+            write: None,
+        }
+    }
+
+    pub fn earliest(&self) -> u64 {
+        self.start
+    }
+
+    pub fn push(&mut self, entry: Entry<N>) {
+        self.entries.push_back(entry);
+    }
+
+    pub fn is_checkpoint_due(&self, step: u64) -> bool {
+        (step - self.origin).is_multiple_of(self.recording.interval.max(1))
+    }
+
+    pub fn checkpoint(&mut self, checkpoint: Checkpoint<N>) {
+        self.checkpoints.push_back(checkpoint);
+
+        if self.checkpoints.len() > self.recording.checkpoints.max(1) {
+            self.checkpoints.pop_front();
+
+            let oldest = self.checkpoints[0].step;
+            let dropped = (oldest - self.start) as usize;
+            self.entries.drain(..dropped);
+            self.start = oldest;
+        }
+    }
+
+    /// Takes the entry for the last step, forgetting any checkpoint after it.
+    pub fn pop(&mut self) -> Option<Entry<N>> {
+        let entry = self.entries.pop_back()?;
+
+        let step = self.start + self.entries.len() as u64;
+        while self.checkpoints.back().is_some_and(|c| c.step > step) {
+            self.checkpoints.pop_back();
+        }
+
+        Some(entry)
+    }
+
+    /// The first checkpoint at or after `step`.
+    pub fn checkpoint_from(&self, step: u64) -> Option<&Checkpoint<N>> {
+        self.checkpoints.iter().find(|c| c.step >= step)
+    }
+
+    /// Forgets everything after `step`, returning the inputs those steps
+    /// consumed, in order.
+    pub fn truncate(&mut self, step: u64) -> Vec<N> {
+        let inputs = self
+            .entries
+            .drain((step - self.start) as usize..)
+            .filter_map(|entry| entry.input)
+            .collect();
+
+        while self.checkpoints.back().is_some_and(|c| c.step > step) {
+            self.checkpoints.pop_back();
+        }
+
+        inputs
+    }
+}
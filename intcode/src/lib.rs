@@ -0,0 +1,39 @@
+pub mod analysis;
+mod ascii;
+pub mod assembler;
+mod budget;
+mod builder;
+mod cached;
+pub mod debugger;
+pub mod disassembler;
+mod error;
+pub mod fuzz;
+mod hooks;
+mod io;
+mod journal;
+mod machine;
+mod memory;
+pub mod network;
+mod numeric;
+mod op_code;
+mod patch;
+pub mod profiler;
+mod program;
+pub mod snapshot;
+mod trace;
+
+pub use ascii::*;
+pub use budget::*;
+pub use builder::*;
+pub use cached::*;
+pub use error::*;
+pub use hooks::*;
+pub use io::*;
+pub use journal::{Recording, RewindError, DEFAULT_CHECKPOINTS, DEFAULT_CHECKPOINT_INTERVAL};
+pub use machine::*;
+pub use memory::*;
+pub use numeric::*;
+pub use op_code::{OpCode, OpKind, ParameterMode};
+pub use patch::*;
+pub use program::*;
+pub use trace::*;
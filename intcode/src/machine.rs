@@ -0,0 +1,1425 @@
+use crate::budget::{Budget, Limit};
+use crate::builder::MachineBuilder;
+use crate::error::MachineError;
+use crate::hooks::Hooks;
+use crate::journal::{Checkpoint, Entry, Journal, Recording, RewindError};
+use crate::memory::{Memory, MemoryModel};
+use crate::numeric::Number;
+use crate::op_code::*;
+use crate::snapshot::{Snapshot, SnapshotError};
+use crate::trace::{NoTracer, Tracer};
+
+/// An intcode machine computing with `N`s. The default `i64` is what every
+/// puzzle needs and faults on overflow, as does `i128`, while `BigInt` never
+/// overflows. Whatever `N` is, op codes, addresses and the relative base must
+/// fit in an `i64`.
+#[derive(Clone)]
+pub struct OpCodeMachine<T = NoTracer, N = i64> {
+    data: Memory<N>,
+    inputs: Vec<N>,
+    pointer: usize,
+    relative_base: isize,
+    pub is_complete: bool,
+    budget: Budget,
+    steps: u64,
+    journal: Option<Journal<N>>,
+    hooks: Hooks<N>,
+    tracer: T,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MachineResult<N = i64> {
+    Halt,
+    InputRequired,
+    Output(N),
+    Fault(MachineError),
+    /// The machine stopped before an instruction that would have gone over
+    /// its budget.
+    BudgetExceeded(Limit),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum StepResult<N = i64> {
+    Executed(Step<N>),
+    InputRequired,
+    /// The machine had already halted, so nothing was executed.
+    Halted,
+    Fault(MachineError),
+    BudgetExceeded(Limit),
+}
+
+// Why `execute` didn't finish an instruction.
+enum Stop {
+    Fault(MachineError),
+    Budget(Limit),
+}
+
+impl From<MachineError> for Stop {
+    fn from(error: MachineError) -> Stop {
+        Stop::Fault(error)
+    }
+}
+
+/// An instruction that `OpCodeMachine::step` has executed.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Step<N = i64> {
+    pub address: usize,
+    pub op_code: OpCode,
+    /// The resolved value of each parameter, or the address for a parameter
+    /// that is written to. A jump target is only resolved if it's taken.
+    pub operands: [N; 3],
+    pub effect: Effect<N>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Effect<N = i64> {
+    Write {
+        address: usize,
+        value: N,
+    },
+    Input {
+        address: usize,
+        value: N,
+    },
+    Output(N),
+    /// The jump target, or `None` if the jump wasn't taken.
+    Jump(Option<usize>),
+    /// The new relative base.
+    RelativeBase(isize),
+    Halt,
+}
+
+impl OpCodeMachine {
+    pub fn new(data: Vec<i64>) -> OpCodeMachine {
+        OpCodeMachine::from_memory(Memory::from(data))
+    }
+
+    pub fn builder(program: Vec<i64>) -> MachineBuilder {
+        MachineBuilder::new(program)
+    }
+
+    /// Fails if the snapshot has sparse values where its memory model keeps
+    /// memory dense.
+    pub fn from_snapshot(snapshot: Snapshot) -> Result<OpCodeMachine, SnapshotError> {
+        let dense_limit = snapshot.memory_model.dense_limit();
+        if snapshot
+            .sparse
+            .iter()
+            .any(|(address, _)| *address < dense_limit)
+        {
+            return Err(SnapshotError::InvalidValue("sparse address"));
+        }
+
+        let mut data = Memory::with_model(snapshot.memory, snapshot.memory_model);
+        for (address, value) in snapshot.sparse {
+            data.write(address, value);
+        }
+
+        Ok(OpCodeMachine {
+            data,
+            inputs: snapshot.inputs,
+            pointer: snapshot.pointer,
+            relative_base: snapshot.relative_base,
+            is_complete: snapshot.is_complete,
+            budget: Budget::default(),
+            steps: 0,
+            journal: None,
+            hooks: Hooks::default(),
+            tracer: NoTracer,
+        })
+    }
+}
+
+impl<T: Tracer> OpCodeMachine<T> {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            version: Snapshot::VERSION,
+            memory: self.data.dense(),
+            sparse: self.data.sparse().collect(),
+            memory_model: self.data.model(),
+            inputs: self.inputs.clone(),
+            pointer: self.pointer,
+            relative_base: self.relative_base,
+            is_complete: self.is_complete,
+        }
+    }
+}
+
+impl<N: Number> OpCodeMachine<NoTracer, N> {
+    pub fn from_memory(data: Memory<N>) -> OpCodeMachine<NoTracer, N> {
+        OpCodeMachine {
+            data,
+            inputs: Vec::new(),
+            pointer: 0,
+            relative_base: 0,
+            is_complete: false,
+            budget: Budget::default(),
+            steps: 0,
+            journal: None,
+            hooks: Hooks::default(),
+            tracer: NoTracer,
+        }
+    }
+
+    /// Widens an ordinary program to `N`s, e.g.
+    /// `OpCodeMachine::<NoTracer, BigInt>::from_program(&program)`.
+    pub fn from_program(program: &[i64]) -> OpCodeMachine<NoTracer, N> {
+        let values = program.iter().map(|&value| N::from(value)).collect();
+        OpCodeMachine::from_memory(Memory::from_values(values, MemoryModel::Dense))
+    }
+}
+
+impl<T: Tracer<N>, N: Number> OpCodeMachine<T, N> {
+    pub fn with_input(mut self, input: N) -> OpCodeMachine<T, N> {
+        self.inputs.push(input);
+        self
+    }
+
+    /// Replaces the tracer, which is given every instruction as it executes.
+    pub fn with_tracer<U: Tracer<N>>(self, tracer: U) -> OpCodeMachine<U, N> {
+        OpCodeMachine {
+            data: self.data,
+            inputs: self.inputs,
+            pointer: self.pointer,
+            relative_base: self.relative_base,
+            is_complete: self.is_complete,
+            budget: self.budget,
+            steps: self.steps,
+            journal: self.journal,
+            hooks: self.hooks,
+            tracer,
+        }
+    }
+
+    pub fn with_budget(mut self, budget: Budget) -> OpCodeMachine<T, N> {
+        self.budget = budget;
+        self
+    }
+
+    pub fn set_budget(&mut self, budget: Budget) {
+        self.budget = budget;
+    }
+
+    pub fn budget(&self) -> Budget {
+        self.budget
+    }
+
+    /// How many instructions the machine has executed.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Starts keeping the history needed to step backwards, from this step on.
+    pub fn record(&mut self, recording: Recording) {
+        self.journal = Some(Journal::new(recording, self.checkpoint()));
+    }
+
+    pub fn stop_recording(&mut self) {
+        self.journal = None;
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// The furthest step back the machine can rewind to, if it's recording.
+    pub fn earliest_step(&self) -> Option<u64> {
+        self.journal.as_ref().map(Journal::earliest)
+    }
+
+    /// Undoes the last instruction, putting back any input it consumed.
+    /// Returns false if there's no history to undo.
+    pub fn step_back(&mut self) -> bool {
+        let entry = match self.journal.as_mut().and_then(Journal::pop) {
+            Some(entry) => entry,
+            None => return false,
+        };
+
+        if let Some((address, value)) = entry.write {
+            self.data.write(address, value);
+            self.data.truncate(entry.len);
+        }
+        if let Some(value) = entry.input {
+            self.inputs.insert(0, value);
+        }
+
+        self.pointer = entry.pointer;
+        self.relative_base = entry.relative_base;
+        self.is_complete = false;
+        self.steps -= 1;
+        true
+    }
+
+    /// Puts the machine back to how it was after `step` instructions.
+    pub fn rewind_to(&mut self, step: u64) -> Result<(), RewindError> {
+        let journal = self.journal.as_mut().ok_or(RewindError::NotRecording)?;
+        if step > self.steps {
+            return Err(RewindError::InFuture {
+                current: self.steps,
+            });
+        }
+        if step < journal.earliest() {
+            return Err(RewindError::TooFarBack {
+                earliest: journal.earliest(),
+            });
+        }
+
+        let current = self.steps;
+        let checkpoint = journal
+            .checkpoint_from(step)
+            .filter(|checkpoint| checkpoint.step < current)
+            .cloned();
+        if let Some(checkpoint) = checkpoint {
+            let inputs = journal.truncate(checkpoint.step);
+            self.inputs.splice(0..0, inputs);
+
+            self.data = checkpoint.data;
+            self.pointer = checkpoint.pointer;
+            self.relative_base = checkpoint.relative_base;
+            self.is_complete = checkpoint.is_complete;
+            self.steps = checkpoint.step;
+        }
+
+        while self.steps > step {
+            self.step_back();
+        }
+
+        Ok(())
+    }
+
+    fn checkpoint(&self) -> Checkpoint<N> {
+        Checkpoint {
+            step: self.steps,
+            data: self.data.clone(),
+            pointer: self.pointer,
+            relative_base: self.relative_base,
+            is_complete: self.is_complete,
+        }
+    }
+
+    pub fn hooks(&self) -> &Hooks<N> {
+        &self.hooks
+    }
+
+    /// The hooks to call as the machine runs.
+    pub fn hooks_mut(&mut self) -> &mut Hooks<N> {
+        &mut self.hooks
+    }
+
+    pub fn tracer(&self) -> &T {
+        &self.tracer
+    }
+
+    pub fn tracer_mut(&mut self) -> &mut T {
+        &mut self.tracer
+    }
+
+    pub fn into_tracer(self) -> T {
+        self.tracer
+    }
+
+    /// Clones the machine without sharing any memory pages with it.
+    pub fn deep_clone(&self) -> OpCodeMachine<T, N>
+    where
+        T: Clone,
+    {
+        let mut machine = self.clone();
+        machine.data.unshare();
+        machine
+    }
+
+    pub fn input(&mut self, input: N) {
+        self.inputs.push(input);
+    }
+
+    pub fn pointer(&self) -> usize {
+        self.pointer
+    }
+
+    pub fn relative_base(&self) -> isize {
+        self.relative_base
+    }
+
+    pub fn memory(&self) -> &Memory<N> {
+        &self.data
+    }
+
+    pub fn inputs(&self) -> &[N] {
+        &self.inputs
+    }
+
+    /// Runs until the machine produces output, needs input, halts or faults.
+    /// A faulting instruction is not applied, so the pointer is left on it and
+    /// the memory is as it was just before it.
+    pub fn run(&mut self) -> MachineResult<N> {
+        let mut remaining = self.budget.steps_per_run;
+
+        loop {
+            if remaining == Some(0) && !self.is_complete {
+                return MachineResult::BudgetExceeded(Limit::StepsPerRun);
+            }
+            remaining = remaining.map(|steps| steps - 1);
+
+            match self.step() {
+                StepResult::Executed(step) => match step.effect {
+                    Effect::Output(value) => return MachineResult::Output(value),
+                    Effect::Halt => return MachineResult::Halt,
+                    _ => continue,
+                },
+                StepResult::InputRequired => return MachineResult::InputRequired,
+                StepResult::Halted => return MachineResult::Halt,
+                StepResult::Fault(error) => return MachineResult::Fault(error),
+                StepResult::BudgetExceeded(limit) => return MachineResult::BudgetExceeded(limit),
+            }
+        }
+    }
+
+    /// Executes a single instruction.
+    pub fn step(&mut self) -> StepResult<N> {
+        if self.is_complete {
+            return StepResult::Halted;
+        }
+
+        if self
+            .budget
+            .total_steps
+            .is_some_and(|total| self.steps >= total)
+        {
+            return StepResult::BudgetExceeded(Limit::TotalSteps);
+        }
+
+        let pointer = self.pointer;
+        let relative_base = self.relative_base;
+        let len = self.data.len();
+
+        match self.execute() {
+            Ok(Some(step)) => {
+                self.steps += 1;
+
+                if let Some(journal) = &mut self.journal {
+                    let input = match &step.effect {
+                        Effect::Input { value, .. } => Some(value.clone()),
+                        _ => None,
+                    };
+                    let write = journal.write.take();
+                    journal.push(Entry {
+                        pointer,
+                        relative_base,
+                        len,
+                        write,
+                        input,
+                    });
+
+                    if journal.is_checkpoint_due(self.steps) {
+                        let checkpoint = self.checkpoint();
+                        if let Some(journal) = &mut self.journal {
+                            journal.checkpoint(checkpoint);
+                        }
+                    }
+                }
+
+                StepResult::Executed(step)
+            }
+            Ok(None) => StepResult::InputRequired,
+            Err(Stop::Fault(error)) => StepResult::Fault(error),
+            Err(Stop::Budget(limit)) => StepResult::BudgetExceeded(limit),
+        }
+    }
+
+    fn execute(&mut self) -> Result<Option<Step<N>>, Stop> {
+        let address = self.pointer;
+        let value = match self.data.get(address) {
+            Some(value) => value
+                .to_i64()
+                .ok_or(MachineError::ValueOutOfRange { address })?,
+            None => return Err(MachineError::PointerOutOfRange(address as i64).into()),
+        };
+
+        let op_code = get_op(value, address)?;
+        let zero = || N::from(0);
+
+        let (operands, effect) = match op_code {
+            OpCode::Add(p1_mode, p2_mode, p3_mode) => {
+                let p1 = self.get_data(address + 1, p1_mode)?;
+                let p2 = self.get_data(address + 2, p2_mode)?;
+                let p3 = self.get_write_index(address + 3, p3_mode)?;
+
+                let value = p1
+                    .checked_add(&p2)
+                    .ok_or(MachineError::Overflow { address })?;
+                ([p1, p2, N::from(p3 as i64)], self.write(p3, value))
+            }
+            OpCode::Multiply(p1_mode, p2_mode, p3_mode) => {
+                let p1 = self.get_data(address + 1, p1_mode)?;
+                let p2 = self.get_data(address + 2, p2_mode)?;
+                let p3 = self.get_write_index(address + 3, p3_mode)?;
+
+                let value = p1
+                    .checked_mul(&p2)
+                    .ok_or(MachineError::Overflow { address })?;
+                ([p1, p2, N::from(p3 as i64)], self.write(p3, value))
+            }
+            OpCode::Input(p1_mode) => {
+                let p1 = self.get_write_index(address + 1, p1_mode)?;
+
+                if self.inputs.is_empty() {
+                    return Ok(None);
+                }
+
+                let value = self.inputs.remove(0);
+                self.set_value(p1, value.clone());
+
+                (
+                    [N::from(p1 as i64), zero(), zero()],
+                    Effect::Input { address: p1, value },
+                )
+            }
+            OpCode::Output(p1_mode) => {
+                let p1 = self.get_data(address + 1, p1_mode)?;
+
+                ([p1.clone(), zero(), zero()], Effect::Output(p1))
+            }
+            OpCode::JumpIfTrue(p1_mode, p2_mode) => {
+                let p1 = self.get_data(address + 1, p1_mode)?;
+
+                if p1.is_zero() {
+                    ([p1, zero(), zero()], Effect::Jump(None))
+                } else {
+                    let p2 = self.get_jump_target(address + 2, p2_mode)?;
+                    ([p1, N::from(p2 as i64), zero()], Effect::Jump(Some(p2)))
+                }
+            }
+            OpCode::JumpIfFalse(p1_mode, p2_mode) => {
+                let p1 = self.get_data(address + 1, p1_mode)?;
+
+                if p1.is_zero() {
+                    let p2 = self.get_jump_target(address + 2, p2_mode)?;
+                    ([p1, N::from(p2 as i64), zero()], Effect::Jump(Some(p2)))
+                } else {
+                    ([p1, zero(), zero()], Effect::Jump(None))
+                }
+            }
+            OpCode::LessThan(p1_mode, p2_mode, p3_mode) => {
+                let p1 = self.get_data(address + 1, p1_mode)?;
+                let p2 = self.get_data(address + 2, p2_mode)?;
+                let p3 = self.get_write_index(address + 3, p3_mode)?;
+
+                let value = N::from((p1 < p2) as i64);
+                ([p1, p2, N::from(p3 as i64)], self.write(p3, value))
+            }
+            OpCode::Equals(p1_mode, p2_mode, p3_mode) => {
+                let p1 = self.get_data(address + 1, p1_mode)?;
+                let p2 = self.get_data(address + 2, p2_mode)?;
+                let p3 = self.get_write_index(address + 3, p3_mode)?;
+
+                let value = N::from((p1 == p2) as i64);
+                ([p1, p2, N::from(p3 as i64)], self.write(p3, value))
+            }
+            OpCode::AdjustRelativeBase(p1_mode) => {
+                let p1 = self.get_data(address + 1, p1_mode)?;
+                let offset = p1
+                    .to_i64()
+                    .ok_or(MachineError::ValueOutOfRange { address })?;
+
+                let old = self.relative_base;
+                self.relative_base = old
+                    .checked_add(offset as isize)
+                    .ok_or(MachineError::Overflow { address })?;
+                if !self.hooks.is_empty() {
+                    self.hooks.relative_base(address, old, self.relative_base);
+                }
+                (
+                    [p1, zero(), zero()],
+                    Effect::RelativeBase(self.relative_base),
+                )
+            }
+            OpCode::Halt => {
+                self.is_complete = true;
+                ([zero(), zero(), zero()], Effect::Halt)
+            }
+        };
+
+        self.pointer = match effect {
+            Effect::Jump(Some(target)) => target,
+            Effect::Halt => address,
+            _ => address + op_code.size(),
+        };
+
+        let step = Step {
+            address,
+            op_code,
+            operands,
+            effect,
+        };
+        self.tracer.trace(&step);
+        if !self.hooks.is_empty() {
+            self.hooks.executed(&step);
+        }
+
+        Ok(Some(step))
+    }
+
+    fn write(&mut self, location: usize, value: N) -> Effect<N> {
+        Effect::Write {
+            address: location,
+            value: self.set_value(location, value),
+        }
+    }
+
+    /// Returns the value written, which a hook may have changed.
+    fn set_value(&mut self, location: usize, mut value: N) -> N {
+        if !self.hooks.is_empty() || self.journal.is_some() {
+            let old = self.data.read(location);
+            if !self.hooks.is_empty() {
+                value = self.hooks.write(self.pointer, location, &old, value);
+            }
+
+            if let Some(journal) = &mut self.journal {
+                journal.write = Some((location, old));
+            }
+        }
+
+        self.data.write(location, value.clone());
+        value
+    }
+
+    fn get_data(&self, location: usize, mode: ParameterMode) -> Result<N, MachineError> {
+        let index = self.get_index(location, mode)?;
+        let value = self.data.read(index);
+        if mode != ParameterMode::Immediate && !self.hooks.is_empty() {
+            self.hooks.read(self.pointer, index, &value);
+        }
+
+        Ok(value)
+    }
+
+    fn get_write_index(&self, location: usize, mode: ParameterMode) -> Result<usize, Stop> {
+        let index = match mode {
+            ParameterMode::Immediate => {
+                return Err(MachineError::ImmediateModeWrite {
+                    address: self.pointer,
+                }
+                .into())
+            }
+            _ => self.get_index(location, mode)?,
+        };
+
+        match self.budget.memory {
+            Some(size) if index >= size && index >= self.data.len() => {
+                Err(Stop::Budget(Limit::Memory))
+            }
+            _ => Ok(index),
+        }
+    }
+
+    fn get_jump_target(&self, location: usize, mode: ParameterMode) -> Result<usize, MachineError> {
+        let target =
+            self.get_data(location, mode)?
+                .to_i64()
+                .ok_or(MachineError::ValueOutOfRange {
+                    address: self.pointer,
+                })?;
+
+        if target < 0 || target as usize >= self.data.len() {
+            return Err(MachineError::PointerOutOfRange(target));
+        }
+
+        Ok(target as usize)
+    }
+
+    fn get_index(&self, location: usize, mode: ParameterMode) -> Result<usize, MachineError> {
+        let address = self.pointer;
+        let index = match mode {
+            ParameterMode::Position => self.data.read(location),
+            ParameterMode::Immediate => return Ok(location),
+            ParameterMode::Relative => self
+                .data
+                .read(location)
+                .checked_add(&N::from(self.relative_base as i64))
+                .ok_or(MachineError::Overflow { address })?,
+        };
+
+        let index = index
+            .to_i64()
+            .ok_or(MachineError::ValueOutOfRange { address })?;
+        if index < 0 {
+            return Err(MachineError::NegativeAddress {
+                address,
+                target: index,
+            });
+        }
+
+        Ok(index as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    mod run {
+        use super::super::*;
+
+        const EQUAL_TO_8_DATA_POSITION: &[i64] = &[3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+
+        #[test]
+        fn example_equal_8_position_mode_false() {
+            let mut machine = OpCodeMachine::new(EQUAL_TO_8_DATA_POSITION.to_vec()).with_input(5);
+
+            assert_eq!(machine.run(), MachineResult::Output(0));
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+
+        #[test]
+        fn example_equal_8_position_mode_true() {
+            let mut machine = OpCodeMachine::new(EQUAL_TO_8_DATA_POSITION.to_vec()).with_input(8);
+
+            assert_eq!(machine.run(), MachineResult::Output(1));
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+
+        const EQUAL_TO_8_DATA_IMMEDIATE: &[i64] = &[3, 3, 1108, -1, 8, 3, 4, 3, 99];
+
+        #[test]
+        fn example_equal_8_immediate_mode_false() {
+            let mut machine = OpCodeMachine::new(EQUAL_TO_8_DATA_IMMEDIATE.to_vec()).with_input(5);
+
+            assert_eq!(machine.run(), MachineResult::Output(0));
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+
+        #[test]
+        fn example_equal_8_immediate_mode_true() {
+            let mut machine = OpCodeMachine::new(EQUAL_TO_8_DATA_IMMEDIATE.to_vec()).with_input(8);
+
+            assert_eq!(machine.run(), MachineResult::Output(1));
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+
+        const LESS_THAN_8_DATA_POSITION: &[i64] = &[3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8];
+
+        #[test]
+        fn less_than_8_position_mode_false() {
+            let mut machine = OpCodeMachine::new(LESS_THAN_8_DATA_POSITION.to_vec()).with_input(10);
+
+            assert_eq!(machine.run(), MachineResult::Output(0));
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+
+        #[test]
+        fn less_than_8_position_mode_true() {
+            let mut machine = OpCodeMachine::new(LESS_THAN_8_DATA_POSITION.to_vec()).with_input(5);
+
+            assert_eq!(machine.run(), MachineResult::Output(1));
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+
+        const LESS_THAN_8_DATA_IMMEDIATE: &[i64] = &[3, 3, 1107, -1, 8, 3, 4, 3, 99];
+
+        #[test]
+        fn less_than_8_immediate_mode_false() {
+            let mut machine =
+                OpCodeMachine::new(LESS_THAN_8_DATA_IMMEDIATE.to_vec()).with_input(10);
+
+            assert_eq!(machine.run(), MachineResult::Output(0));
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+
+        #[test]
+        fn less_than_8_immediate_mode_true() {
+            let mut machine = OpCodeMachine::new(LESS_THAN_8_DATA_IMMEDIATE.to_vec()).with_input(5);
+
+            assert_eq!(machine.run(), MachineResult::Output(1));
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+
+        #[test]
+        fn jump_0_position() {
+            let mut machine = OpCodeMachine::new(vec![
+                3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9,
+            ])
+            .with_input(0);
+
+            assert_eq!(machine.run(), MachineResult::Output(0));
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+
+        #[test]
+        fn jump_0_immediate() {
+            let mut machine =
+                OpCodeMachine::new(vec![3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1])
+                    .with_input(0);
+
+            assert_eq!(machine.run(), MachineResult::Output(0));
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+
+        const LARGE_EXAMPLE_DATA: &[i64] = &[
+            3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0,
+            0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4,
+            20, 1105, 1, 46, 98, 99,
+        ];
+
+        #[test]
+        fn large_example_input_less_than() {
+            let mut machine = OpCodeMachine::new(LARGE_EXAMPLE_DATA.to_vec()).with_input(5);
+
+            assert_eq!(machine.run(), MachineResult::Output(999));
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+
+        #[test]
+        fn large_example_input_equal() {
+            let mut machine = OpCodeMachine::new(LARGE_EXAMPLE_DATA.to_vec()).with_input(8);
+
+            assert_eq!(machine.run(), MachineResult::Output(1000));
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+
+        #[test]
+        fn large_example_input_greater_than() {
+            let mut machine = OpCodeMachine::new(LARGE_EXAMPLE_DATA.to_vec()).with_input(10);
+
+            assert_eq!(machine.run(), MachineResult::Output(1001));
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+
+        #[test]
+        fn large_value_support_example() {
+            let mut machine = OpCodeMachine::new(vec![104, 1125899906842624, 99]);
+            assert_eq!(machine.run(), MachineResult::Output(1125899906842624));
+        }
+
+        #[test]
+        fn quine_example() {
+            let source = vec![
+                109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+            ];
+            let mut machine = OpCodeMachine::new(source.clone());
+
+            for value in source.iter() {
+                assert_eq!(machine.run(), MachineResult::Output(*value));
+            }
+        }
+
+        #[test]
+        fn sixteen_digit_output_example() {
+            let mut machine = OpCodeMachine::new(vec![1102, 34915192, 34915192, 7, 4, 7, 99, 0]);
+            assert_eq!(machine.run(), MachineResult::Output(1219070632396864));
+        }
+
+        #[test]
+        fn extra_1_example() {
+            let mut machine = OpCodeMachine::new(vec![109, -1, 4, 1, 99]);
+            assert_eq!(machine.run(), MachineResult::Output(-1));
+        }
+
+        #[test]
+        fn extra_2_example() {
+            let mut machine = OpCodeMachine::new(vec![109, -1, 104, 1, 99]);
+            assert_eq!(machine.run(), MachineResult::Output(1));
+        }
+
+        #[test]
+        fn extra_3_example() {
+            let mut machine = OpCodeMachine::new(vec![109, -1, 204, 1, 99]);
+            assert_eq!(machine.run(), MachineResult::Output(109));
+        }
+
+        #[test]
+        fn extra_4_example() {
+            let mut machine = OpCodeMachine::new(vec![109, 1, 9, 2, 204, -6, 99]);
+            assert_eq!(machine.run(), MachineResult::Output(204));
+        }
+
+        #[test]
+        fn extra_5_example() {
+            let mut machine = OpCodeMachine::new(vec![109, 1, 109, 9, 204, -6, 99]);
+            assert_eq!(machine.run(), MachineResult::Output(204));
+        }
+
+        #[test]
+        fn extra_6_example() {
+            let mut machine = OpCodeMachine::new(vec![109, 1, 209, -1, 204, -106, 99]);
+            assert_eq!(machine.run(), MachineResult::Output(204));
+        }
+
+        #[test]
+        fn extra_7_example() {
+            let mut machine = OpCodeMachine::new(vec![109, 1, 3, 3, 204, 2, 99]).with_input(42);
+            assert_eq!(machine.run(), MachineResult::Output(42));
+        }
+
+        #[test]
+        fn extra_8_example() {
+            let mut machine = OpCodeMachine::new(vec![109, 1, 203, 2, 204, 2, 99]).with_input(42);
+            assert_eq!(machine.run(), MachineResult::Output(42));
+        }
+    }
+
+    mod faults {
+        use super::super::*;
+
+        #[test]
+        fn invalid_op_code() {
+            let mut machine = OpCodeMachine::new(vec![1101, 1, 1, 5, 42, 0]);
+
+            let expected = MachineError::InvalidOpCode {
+                address: 4,
+                value: 42,
+            };
+            assert_eq!(machine.run(), MachineResult::Fault(expected.clone()));
+            assert_eq!(machine.pointer(), 4);
+            assert_eq!(machine.memory().to_vec(), vec![1101, 1, 1, 5, 42, 2]);
+
+            // The machine stays on the faulting instruction
+            assert_eq!(machine.run(), MachineResult::Fault(expected));
+        }
+
+        #[test]
+        fn invalid_parameter_mode() {
+            let mut machine = OpCodeMachine::new(vec![304, 0, 99]);
+
+            assert_eq!(
+                machine.run(),
+                MachineResult::Fault(MachineError::InvalidParameterMode {
+                    address: 0,
+                    mode: 3
+                })
+            );
+        }
+
+        #[test]
+        fn immediate_mode_write() {
+            let mut machine = OpCodeMachine::new(vec![11101, 1, 1, 0, 99]);
+
+            assert_eq!(
+                machine.run(),
+                MachineResult::Fault(MachineError::ImmediateModeWrite { address: 0 })
+            );
+            assert_eq!(machine.memory().to_vec(), vec![11101, 1, 1, 0, 99]);
+        }
+
+        #[test]
+        fn immediate_mode_input_keeps_input() {
+            let mut machine = OpCodeMachine::new(vec![103, 0, 99]).with_input(5);
+
+            assert_eq!(
+                machine.run(),
+                MachineResult::Fault(MachineError::ImmediateModeWrite { address: 0 })
+            );
+            assert_eq!(machine.memory().to_vec(), vec![103, 0, 99]);
+        }
+
+        #[test]
+        fn negative_address_from_relative_base() {
+            let mut machine = OpCodeMachine::new(vec![109, -10, 204, 0, 99]);
+
+            assert_eq!(
+                machine.run(),
+                MachineResult::Fault(MachineError::NegativeAddress {
+                    address: 2,
+                    target: -10
+                })
+            );
+            assert_eq!(machine.pointer(), 2);
+            assert_eq!(machine.relative_base(), -10);
+        }
+
+        #[test]
+        fn negative_address_from_position() {
+            let mut machine = OpCodeMachine::new(vec![4, -1, 99]);
+
+            assert_eq!(
+                machine.run(),
+                MachineResult::Fault(MachineError::NegativeAddress {
+                    address: 0,
+                    target: -1
+                })
+            );
+        }
+
+        #[test]
+        fn overflow() {
+            let mut machine = OpCodeMachine::new(vec![1101, 1, 1, 9, 1102, 1 << 62, 2, 9, 99, 0]);
+
+            assert_eq!(
+                machine.run(),
+                MachineResult::Fault(MachineError::Overflow { address: 4 })
+            );
+            assert_eq!(machine.pointer(), 4);
+            assert_eq!(machine.memory().read(9), 2);
+        }
+
+        #[test]
+        fn relative_base_overflow() {
+            let mut machine = OpCodeMachine::new(vec![109, i64::MAX, 109, 1, 99]);
+
+            assert_eq!(
+                machine.run(),
+                MachineResult::Fault(MachineError::Overflow { address: 2 })
+            );
+            assert_eq!(machine.relative_base(), i64::MAX as isize);
+        }
+
+        #[test]
+        fn run_off_end_of_memory() {
+            let mut machine = OpCodeMachine::new(vec![104, 7]);
+
+            assert_eq!(machine.run(), MachineResult::Output(7));
+            assert_eq!(
+                machine.run(),
+                MachineResult::Fault(MachineError::PointerOutOfRange(2))
+            );
+            assert!(!machine.is_complete);
+        }
+
+        #[test]
+        fn jump_out_of_range() {
+            let mut machine = OpCodeMachine::new(vec![1105, 1, -5, 99]);
+
+            assert_eq!(
+                machine.run(),
+                MachineResult::Fault(MachineError::PointerOutOfRange(-5))
+            );
+            assert_eq!(machine.pointer(), 0);
+        }
+    }
+
+    mod memory_model {
+        use super::super::*;
+        use crate::memory::MemoryModel;
+
+        const TERA: i64 = 1_000_000_000_000;
+
+        // Writes 5 + 6 to relative base + 0 and then outputs it.
+        fn far_write(base: i64) -> Vec<i64> {
+            vec![109, base, 21101, 5, 6, 0, 204, 0, 99]
+        }
+
+        #[test]
+        fn hybrid_far_write() {
+            let mut machine = OpCodeMachine::builder(far_write(TERA))
+                .memory_model(MemoryModel::hybrid())
+                .build();
+
+            assert_eq!(machine.run(), MachineResult::Output(11));
+            assert_eq!(machine.run(), MachineResult::Halt);
+            assert_eq!(machine.memory().len(), TERA as usize + 1);
+            assert_eq!(machine.memory().read(TERA as usize), 11);
+            assert_eq!(machine.memory().page_count(), 1);
+        }
+
+        #[test]
+        fn hybrid_matches_dense() {
+            let mut dense = OpCodeMachine::new(far_write(1000));
+            let mut hybrid = OpCodeMachine::builder(far_write(1000))
+                .memory_model(MemoryModel::Hybrid { dense_limit: 4 })
+                .build();
+
+            assert_eq!(dense.run(), MachineResult::Output(11));
+            assert_eq!(hybrid.run(), MachineResult::Output(11));
+            assert_eq!(hybrid.memory().to_vec(), dense.memory().to_vec());
+        }
+
+        #[test]
+        fn builder_inputs() {
+            let mut machine = OpCodeMachine::builder(vec![3, 0, 3, 1, 4, 1, 99])
+                .input(1)
+                .inputs(vec![2, 3])
+                .build();
+
+            assert_eq!(machine.run(), MachineResult::Output(2));
+            assert_eq!(machine.inputs(), &[3]);
+        }
+
+        #[test]
+        fn snapshot_keeps_sparse_values() {
+            let mut machine = OpCodeMachine::builder(far_write(TERA))
+                .memory_model(MemoryModel::hybrid())
+                .build();
+            machine.run();
+
+            let snapshot = Snapshot::from_bytes(&machine.snapshot().to_bytes()).unwrap();
+            assert_eq!(snapshot.sparse, vec![(TERA as usize, 11)]);
+
+            let restored = OpCodeMachine::from_snapshot(snapshot).unwrap();
+            assert_eq!(restored.memory(), machine.memory());
+            assert_eq!(restored.memory().model(), MemoryModel::hybrid());
+        }
+    }
+
+    mod budget {
+        use super::super::*;
+
+        // Jumps back to itself forever.
+        const SPIN: [i64; 3] = [1105, 1, 0];
+
+        #[test]
+        fn infinite_loop() {
+            let mut machine = OpCodeMachine::new(SPIN.to_vec())
+                .with_budget(Budget::default().steps_per_run(1000));
+
+            assert_eq!(
+                machine.run(),
+                MachineResult::BudgetExceeded(Limit::StepsPerRun)
+            );
+            assert_eq!(machine.steps(), 1000);
+            assert_eq!(machine.pointer(), 0);
+
+            // Each run gets a fresh allowance.
+            assert_eq!(
+                machine.run(),
+                MachineResult::BudgetExceeded(Limit::StepsPerRun)
+            );
+            assert_eq!(machine.steps(), 2000);
+        }
+
+        #[test]
+        fn total_steps() {
+            // Outputs 1 forever.
+            let mut machine = OpCodeMachine::builder(vec![104, 1, 1105, 1, 0])
+                .budget(Budget::default().total_steps(4))
+                .build();
+
+            assert_eq!(machine.run(), MachineResult::Output(1));
+            assert_eq!(machine.run(), MachineResult::Output(1));
+            assert_eq!(
+                machine.run(),
+                MachineResult::BudgetExceeded(Limit::TotalSteps)
+            );
+            assert_eq!(
+                machine.step(),
+                StepResult::BudgetExceeded(Limit::TotalSteps)
+            );
+
+            machine.set_budget(machine.budget().total_steps(6));
+            assert_eq!(machine.run(), MachineResult::Output(1));
+            assert_eq!(
+                machine.run(),
+                MachineResult::BudgetExceeded(Limit::TotalSteps)
+            );
+        }
+
+        #[test]
+        fn memory() {
+            let mut machine = OpCodeMachine::new(vec![1101, 1, 1, 100, 99])
+                .with_budget(Budget::default().memory(50));
+
+            assert_eq!(machine.run(), MachineResult::BudgetExceeded(Limit::Memory));
+            assert_eq!(machine.pointer(), 0);
+            assert_eq!(machine.memory().len(), 5);
+            assert_eq!(machine.steps(), 0);
+
+            machine.set_budget(Budget::default().memory(101));
+            assert_eq!(machine.run(), MachineResult::Halt);
+            assert_eq!(machine.memory().read(100), 2);
+        }
+
+        #[test]
+        fn writes_inside_memory() {
+            let mut machine = OpCodeMachine::new(vec![1101, 1, 1, 0, 99])
+                .with_budget(Budget::default().memory(1));
+
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+
+        #[test]
+        fn halted_machine() {
+            let mut machine = OpCodeMachine::new(vec![99])
+                .with_budget(Budget::default().steps_per_run(1).total_steps(1));
+
+            assert_eq!(machine.run(), MachineResult::Halt);
+            assert_eq!(machine.run(), MachineResult::Halt);
+        }
+    }
+
+    mod journal {
+        use super::super::*;
+        use crate::journal::{Recording, RewindError};
+
+        // Outputs a copy of itself, using the relative base and growing
+        // memory on the way.
+        const QUINE: [i64; 16] = [
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+
+        #[derive(Debug, PartialEq)]
+        struct State {
+            memory: Memory,
+            pointer: usize,
+            relative_base: isize,
+            inputs: Vec<i64>,
+            is_complete: bool,
+        }
+
+        fn state(machine: &OpCodeMachine) -> State {
+            State {
+                memory: machine.memory().clone(),
+                pointer: machine.pointer(),
+                relative_base: machine.relative_base(),
+                inputs: machine.inputs().to_vec(),
+                is_complete: machine.is_complete,
+            }
+        }
+
+        // Runs to the end, returning the state before each step and after
+        // the last one.
+        fn history(machine: &mut OpCodeMachine) -> Vec<State> {
+            let mut states = vec![state(machine)];
+            while let StepResult::Executed(_) = machine.step() {
+                states.push(state(machine));
+            }
+            states
+        }
+
+        #[test]
+        fn step_back() {
+            let mut machine = OpCodeMachine::new(QUINE.to_vec());
+            machine.record(Recording::default());
+            let states = history(&mut machine);
+            assert!(machine.memory().len() > QUINE.len());
+
+            for (step, expected) in states.iter().enumerate().rev() {
+                assert_eq!(machine.steps(), step as u64);
+                assert_eq!(&state(&machine), expected);
+                assert_eq!(machine.step_back(), step > 0);
+            }
+        }
+
+        #[test]
+        fn inputs() {
+            // Adds up three inputs.
+            let mut machine =
+                OpCodeMachine::new(vec![3, 0, 3, 1, 1, 0, 1, 0, 3, 1, 1, 0, 1, 0, 4, 0, 99])
+                    .with_input(1)
+                    .with_input(2)
+                    .with_input(3);
+            machine.record(Recording::default());
+
+            assert_eq!(machine.run(), MachineResult::Output(6));
+            assert!(machine.inputs().is_empty());
+
+            machine.rewind_to(3).unwrap();
+            assert_eq!(machine.inputs(), &[3]);
+
+            machine.rewind_to(0).unwrap();
+            assert_eq!(machine.inputs(), &[1, 2, 3]);
+            assert_eq!(machine.memory().to_vec()[..2], [3, 0]);
+            assert_eq!(machine.run(), MachineResult::Output(6));
+        }
+
+        #[test]
+        fn checkpoints() {
+            let recording = Recording {
+                interval: 5,
+                checkpoints: 3,
+            };
+            let mut machine = OpCodeMachine::new(QUINE.to_vec());
+            machine.record(recording);
+            let states = history(&mut machine);
+            let last = machine.steps();
+
+            // Only the last three intervals are kept.
+            let earliest = machine.earliest_step().unwrap();
+            assert!(last - earliest <= 15 && last - earliest > 10);
+            assert_eq!(earliest % 5, 0);
+
+            assert_eq!(
+                machine.rewind_to(earliest - 1),
+                Err(RewindError::TooFarBack { earliest })
+            );
+            assert_eq!(
+                machine.rewind_to(last + 1),
+                Err(RewindError::InFuture { current: last })
+            );
+
+            for step in (earliest..last).rev().step_by(3) {
+                machine.rewind_to(step).unwrap();
+                assert_eq!(machine.steps(), step);
+                assert_eq!(state(&machine), states[step as usize]);
+            }
+
+            // Running on from a rewind repeats the same steps and keeps
+            // recording them.
+            machine.rewind_to(earliest).unwrap();
+            let replayed = history(&mut machine);
+            assert_eq!(replayed[..], states[earliest as usize..]);
+            machine.rewind_to(last - 7).unwrap();
+            assert_eq!(state(&machine), states[last as usize - 7]);
+        }
+
+        #[test]
+        fn not_recording() {
+            let mut machine = OpCodeMachine::new(QUINE.to_vec());
+            assert_eq!(machine.run(), MachineResult::Output(109));
+
+            assert!(!machine.step_back());
+            assert_eq!(machine.rewind_to(0), Err(RewindError::NotRecording));
+            assert_eq!(machine.earliest_step(), None);
+        }
+    }
+
+    mod numbers {
+        use super::super::*;
+        use crate::assembler::assemble;
+        use crate::budget::Budget;
+        use crate::journal::Recording;
+        use crate::numeric::BigInt;
+        use crate::op_code::OpKind;
+        use std::sync::mpsc::channel;
+
+        fn outputs<T: Tracer<N>, N: Number>(
+            machine: &mut OpCodeMachine<T, N>,
+        ) -> (Vec<N>, MachineResult<N>) {
+            let mut outputs = Vec::new();
+            loop {
+                match machine.run() {
+                    MachineResult::Output(value) => outputs.push(value),
+                    result => return (outputs, result),
+                }
+            }
+        }
+
+        fn big(program: &[i64]) -> OpCodeMachine<NoTracer, BigInt> {
+            OpCodeMachine::from_program(program)
+        }
+
+        // 2^62 * 4, which is 2^64.
+        const PAST_I64: [i64; 8] = [1102, 1 << 62, 4, 7, 4, 7, 99, 0];
+
+        #[test]
+        fn i128() {
+            let mut machine = OpCodeMachine::<NoTracer, i128>::from_program(&PAST_I64);
+
+            assert_eq!(outputs(&mut machine), (vec![1 << 64], MachineResult::Halt));
+        }
+
+        #[test]
+        fn big_int() {
+            assert_eq!(
+                outputs(&mut big(&PAST_I64)),
+                (vec![BigInt::from(1) << 64usize], MachineResult::Halt)
+            );
+        }
+
+        #[test]
+        fn repeated_squares() {
+            let program = assemble(
+                "
+                loop:
+                    MUL  [x], [x], [x]
+                    ADD  [n], #-1, [n]
+                    JT   [n], #loop
+                    OUT  [x]
+                    HLT
+                x:  data 2
+                n:  data 8",
+            )
+            .unwrap();
+
+            let mut machine = OpCodeMachine::<NoTracer, i128>::from_program(&program);
+            assert_eq!(
+                outputs(&mut machine),
+                (
+                    vec![],
+                    MachineResult::Fault(MachineError::Overflow { address: 0 })
+                )
+            );
+
+            assert_eq!(
+                outputs(&mut big(&program)),
+                (vec![BigInt::from(1) << 256usize], MachineResult::Halt)
+            );
+        }
+
+        #[test]
+        fn big_addresses() {
+            let mut machine = OpCodeMachine::from_memory(Memory::from_values(
+                vec![
+                    BigInt::from(4),
+                    BigInt::from(1) << 64usize,
+                    BigInt::from(99),
+                ],
+                MemoryModel::Dense,
+            ));
+
+            assert_eq!(
+                machine.run(),
+                MachineResult::Fault(MachineError::ValueOutOfRange { address: 0 })
+            );
+            assert_eq!(machine.pointer(), 0);
+        }
+
+        #[test]
+        fn matches_i64() {
+            let programs: &[&[i64]] = &[
+                &[
+                    109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+                ],
+                &[1102, 34915192, 34915192, 7, 4, 7, 99, 0],
+                &[3, 0, 99],
+                &[77],
+                &[11101, 1, 1, 0, 99],
+                &[1105, 1, -5, 99],
+                &[104, 7],
+                &[109, -10, 204, 0, 99],
+            ];
+
+            for program in programs {
+                let (values, result) = outputs(&mut OpCodeMachine::new(program.to_vec()));
+                let expected = match result {
+                    MachineResult::Output(value) => MachineResult::Output(BigInt::from(value)),
+                    MachineResult::Halt => MachineResult::Halt,
+                    MachineResult::InputRequired => MachineResult::InputRequired,
+                    MachineResult::Fault(error) => MachineResult::Fault(error),
+                    MachineResult::BudgetExceeded(limit) => MachineResult::BudgetExceeded(limit),
+                };
+
+                assert_eq!(
+                    outputs(&mut big(program)),
+                    (values.into_iter().map(BigInt::from).collect(), expected)
+                );
+            }
+        }
+
+        #[test]
+        fn hybrid_memory() {
+            let tera: i64 = 1_000_000_000_000;
+            let program = vec![1101, 1 << 62, 1 << 62, tera, 4, tera, 99]
+                .into_iter()
+                .map(BigInt::from)
+                .collect();
+            let mut machine =
+                OpCodeMachine::from_memory(Memory::from_values(program, MemoryModel::hybrid()));
+
+            assert_eq!(
+                outputs(&mut machine),
+                (vec![BigInt::from(1) << 63usize], MachineResult::Halt)
+            );
+            assert_eq!(machine.memory().page_count(), 1);
+        }
+
+        #[test]
+        fn tracer_hooks_budget_and_journal() {
+            let mut machine = big(&PAST_I64)
+                .with_tracer(Vec::new())
+                .with_budget(Budget::default().total_steps(2));
+            let (sender, receiver) = channel();
+            machine.hooks_mut().on_op(OpKind::Output, move |event| {
+                sender.send(event.clone()).unwrap()
+            });
+            machine.record(Recording::default());
+
+            let (values, result) = outputs(&mut machine);
+            assert_eq!(values, vec![BigInt::from(1) << 64usize]);
+            assert_eq!(result, MachineResult::BudgetExceeded(Limit::TotalSteps));
+            assert_eq!(machine.tracer().len(), 2);
+            assert_eq!(receiver.try_iter().count(), 1);
+
+            assert!(machine.step_back());
+            assert!(machine.step_back());
+            assert_eq!(machine.memory().read(7), BigInt::from(0));
+            assert_eq!(machine.pointer(), 0);
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use crate::numeric::Number;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+pub const PAGE_SIZE: usize = 128;
+
+/// The dense limit used by `MemoryModel::hybrid`, which is plenty for any
+/// program image plus the memory it works in.
+pub const DEFAULT_DENSE_LIMIT: usize = 1 << 16;
+
+type Page<N> = [N; PAGE_SIZE];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MemoryModel {
+    /// Every address up to the highest one written is stored, so writing to a
+    /// huge address allocates everything below it.
+    #[default]
+    Dense,
+    /// Addresses below `dense_limit` are stored densely and anything above
+    /// that is kept in a map, so far away writes only cost what they use.
+    Hybrid { dense_limit: usize },
+}
+
+impl MemoryModel {
+    pub fn hybrid() -> MemoryModel {
+        MemoryModel::Hybrid {
+            dense_limit: DEFAULT_DENSE_LIMIT,
+        }
+    }
+
+    pub(crate) fn dense_limit(self) -> usize {
+        match self {
+            MemoryModel::Dense => usize::MAX,
+            MemoryModel::Hybrid { dense_limit } => dense_limit,
+        }
+    }
+}
+
+/// Copy-on-write paged memory of `N`s. Cloning only copies the page pointers,
+/// and a page is copied the first time a clone writes to it, so forked
+/// machines share everything they haven't changed.
+#[derive(Debug, Clone)]
+pub struct Memory<N = i64> {
+    model: MemoryModel,
+    pages: Vec<Arc<Page<N>>>,
+    dense_len: usize,
+    sparse: Arc<BTreeMap<usize, N>>,
+    len: usize,
+}
+
+impl<N> Default for Memory<N> {
+    fn default() -> Memory<N> {
+        Memory {
+            model: MemoryModel::default(),
+            pages: Vec::new(),
+            dense_len: 0,
+            sparse: Arc::default(),
+            len: 0,
+        }
+    }
+}
+
+fn zeros<N: Number>() -> Page<N> {
+    std::array::from_fn(|_| N::from(0))
+}
+
+impl Memory {
+    pub fn new() -> Memory {
+        Memory::default()
+    }
+
+    pub fn with_model(data: Vec<i64>, model: MemoryModel) -> Memory {
+        Memory::from_values(data, model)
+    }
+}
+
+impl<N: Number> Memory<N> {
+    /// Like `with_model`, for values other than `i64`.
+    pub fn from_values(data: Vec<N>, model: MemoryModel) -> Memory<N> {
+        let mut memory = Memory {
+            model,
+            ..Memory::default()
+        };
+
+        let dense = data.len().min(model.dense_limit());
+        memory.pages = data[..dense]
+            .chunks(PAGE_SIZE)
+            .map(|chunk| {
+                let mut page = zeros();
+                page[..chunk.len()].clone_from_slice(chunk);
+                Arc::new(page)
+            })
+            .collect();
+        memory.dense_len = dense;
+        memory.len = dense;
+
+        for (address, value) in data.into_iter().enumerate().skip(dense) {
+            memory.write(address, value);
+        }
+
+        memory
+    }
+
+    pub fn model(&self) -> MemoryModel {
+        self.model
+    }
+
+    /// One past the highest address that has been written to.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, address: usize) -> Option<N> {
+        if address >= self.len {
+            None
+        } else if address < self.dense_len {
+            Some(self.pages[address / PAGE_SIZE][address % PAGE_SIZE].clone())
+        } else {
+            Some(
+                self.sparse
+                    .get(&address)
+                    .cloned()
+                    .unwrap_or_else(|| N::from(0)),
+            )
+        }
+    }
+
+    /// Reads a value, treating anything past the end as 0.
+    pub fn read(&self, address: usize) -> N {
+        self.get(address).unwrap_or_else(|| N::from(0))
+    }
+
+    /// Reads up to `count` values from `start`, stopping at the end of memory.
+    pub fn read_range(&self, start: usize, count: usize) -> Vec<N> {
+        let end = self.len.min(start.saturating_add(count));
+        (start..end).map(|address| self.read(address)).collect()
+    }
+
+    /// Writes a value, growing the memory with zeros if needed.
+    pub fn write(&mut self, address: usize, value: N) {
+        if address >= self.model.dense_limit() {
+            Arc::make_mut(&mut self.sparse).insert(address, value);
+        } else {
+            let page = address / PAGE_SIZE;
+            while self.pages.len() <= page {
+                self.pages.push(Arc::new(zeros()));
+            }
+
+            self.dense_len = self.dense_len.max(address + 1);
+            Arc::make_mut(&mut self.pages[page])[address % PAGE_SIZE] = value;
+        }
+
+        self.len = self.len.max(address.saturating_add(1));
+    }
+
+    /// Shrinks the memory to `len` addresses, forgetting anything written at
+    /// or past it.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+
+        if self.dense_len > len {
+            self.pages.truncate(len.div_ceil(PAGE_SIZE));
+            if !len.is_multiple_of(PAGE_SIZE) {
+                let last = self.pages.len() - 1;
+                for value in &mut Arc::make_mut(&mut self.pages[last])[len % PAGE_SIZE..] {
+                    *value = N::from(0);
+                }
+            }
+            self.dense_len = len;
+        }
+
+        if self.sparse.range(len..).next().is_some() {
+            Arc::make_mut(&mut self.sparse).split_off(&len);
+        }
+
+        self.len = len;
+    }
+
+    /// Every value up to `len`, which includes the gaps between sparse values,
+    /// so this isn't suitable for memory with far away writes.
+    pub fn iter(&self) -> impl Iterator<Item = N> + '_ {
+        (0..self.len).map(move |address| self.read(address))
+    }
+
+    pub fn to_vec(&self) -> Vec<N> {
+        self.iter().collect()
+    }
+
+    /// The densely stored values, starting from address 0.
+    pub fn dense(&self) -> Vec<N> {
+        (0..self.dense_len)
+            .map(|address| self.read(address))
+            .collect()
+    }
+
+    /// The values stored outside of the dense region, in address order.
+    pub fn sparse(&self) -> impl Iterator<Item = (usize, N)> + '_ {
+        self.sparse
+            .iter()
+            .map(|(address, value)| (*address, value.clone()))
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// The number of pages this memory shares with `other`.
+    pub fn shared_pages(&self, other: &Memory<N>) -> usize {
+        self.pages
+            .iter()
+            .zip(&other.pages)
+            .filter(|(a, b)| Arc::ptr_eq(a, b))
+            .count()
+    }
+
+    /// Gives this memory its own copy of every page.
+    pub fn unshare(&mut self) {
+        for page in &mut self.pages {
+            Arc::make_mut(page);
+        }
+        Arc::make_mut(&mut self.sparse);
+    }
+}
+
+impl From<Vec<i64>> for Memory {
+    fn from(data: Vec<i64>) -> Memory {
+        Memory::with_model(data, MemoryModel::Dense)
+    }
+}
+
+impl<N: Number> PartialEq for Memory<N> {
+    fn eq(&self, other: &Memory<N>) -> bool {
+        self.len == other.len && self.dense() == other.dense() && self.sparse().eq(other.sparse())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TERA: usize = 1_000_000_000_000;
+
+    #[test]
+    fn truncate() {
+        let mut memory =
+            Memory::with_model(vec![1, 2, 3], MemoryModel::Hybrid { dense_limit: 200 });
+        memory.write(150, 4);
+        memory.write(TERA, 5);
+
+        memory.truncate(151);
+        assert_eq!(memory.len(), 151);
+        assert_eq!(memory.read(150), 4);
+        assert_eq!(memory.sparse().count(), 0);
+
+        memory.truncate(2);
+        assert_eq!(memory.to_vec(), vec![1, 2]);
+        assert_eq!(memory.page_count(), 1);
+
+        // Anything written past the end again starts from zeros.
+        memory.write(4, 6);
+        assert_eq!(memory.to_vec(), vec![1, 2, 0, 0, 6]);
+        assert_eq!(memory, Memory::from(vec![1, 2, 0, 0, 6]));
+    }
+
+    #[test]
+    fn read_write() {
+        let mut memory = Memory::from(vec![1, 2, 3]);
+        assert_eq!(memory.len(), 3);
+        assert_eq!(memory.get(2), Some(3));
+        assert_eq!(memory.get(3), None);
+        assert_eq!(memory.read(1000), 0);
+
+        memory.write(PAGE_SIZE + 1, 7);
+        assert_eq!(memory.len(), PAGE_SIZE + 2);
+        assert_eq!(memory.page_count(), 2);
+        assert_eq!(memory.read(PAGE_SIZE), 0);
+        assert_eq!(memory.read(PAGE_SIZE + 1), 7);
+        assert_eq!(memory.to_vec()[..4], [1, 2, 3, 0]);
+        assert_eq!(memory.read_range(PAGE_SIZE, 10), vec![0, 7]);
+    }
+
+    #[test]
+    fn copy_on_write() {
+        let original = Memory::from((0..PAGE_SIZE as i64 * 3).collect::<Vec<i64>>());
+        let mut fork = original.clone();
+        assert_eq!(fork.shared_pages(&original), 3);
+
+        fork.write(PAGE_SIZE, -1);
+        assert_eq!(fork.shared_pages(&original), 2);
+        assert_eq!(original.read(PAGE_SIZE), PAGE_SIZE as i64);
+        assert_eq!(fork.read(PAGE_SIZE), -1);
+        assert_ne!(fork, original);
+
+        fork.unshare();
+        assert_eq!(fork.shared_pages(&original), 0);
+    }
+
+    #[test]
+    fn hybrid_extreme_addresses() {
+        let mut memory = Memory::with_model(vec![1, 2, 3], MemoryModel::hybrid());
+
+        memory.write(TERA, 42);
+        memory.write(usize::MAX - 1, -42);
+        memory.write(10, 5);
+
+        assert_eq!(memory.len(), usize::MAX);
+        assert_eq!(memory.page_count(), 1);
+        assert_eq!(memory.read(TERA), 42);
+        assert_eq!(memory.read(TERA + 1), 0);
+        assert_eq!(memory.read(usize::MAX - 1), -42);
+        assert_eq!(memory.read(10), 5);
+        assert_eq!(memory.dense().len(), 11);
+        assert_eq!(
+            memory.sparse().collect::<Vec<_>>(),
+            vec![(TERA, 42), (usize::MAX - 1, -42)]
+        );
+    }
+
+    #[test]
+    fn hybrid_split_program() {
+        let model = MemoryModel::Hybrid { dense_limit: 2 };
+        let mut memory = Memory::with_model(vec![1, 2, 3, 4], model);
+
+        assert_eq!(memory.dense(), vec![1, 2]);
+        assert_eq!(memory.sparse().collect::<Vec<_>>(), vec![(2, 3), (3, 4)]);
+        assert_eq!(memory.to_vec(), vec![1, 2, 3, 4]);
+
+        let fork = memory.clone();
+        memory.write(3, 0);
+        assert_eq!(fork.read(3), 4);
+        assert_eq!(memory, Memory::with_model(vec![1, 2, 3, 0], model));
+    }
+}
@@ -0,0 +1,579 @@
+use crate::budget::Limit;
+use crate::error::MachineError;
+use crate::machine::{Effect, OpCodeMachine, StepResult};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+
+/// What a machine is given when it asks for input and has no packets waiting.
+pub const NO_PACKET: i64 = -1;
+
+/// How many instructions a machine may execute before it has to let the next
+/// one run, so a busy machine can't starve the rest of the network.
+pub const DEFAULT_QUANTUM: usize = 1000;
+
+/// A machine is idle once it has asked for input this many times in a row
+/// without getting any or sending anything.
+const IDLE_READS: u32 = 2;
+
+/// A machine is also idle once it has used up this many whole turns in a row
+/// without reading or sending anything, so one stuck in a loop can't keep
+/// the network running forever.
+const IDLE_TURNS: u32 = 100;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Packet {
+    pub destination: i64,
+    pub x: i64,
+    pub y: i64,
+}
+
+/// Watches over the network from its own address.
+pub trait Monitor {
+    /// Receives a packet sent to the monitor's address.
+    fn receive(&mut self, packet: Packet);
+
+    /// Called when every machine is idle with nothing waiting. Returning a
+    /// packet delivers it to wake the network up; returning `None` stops it.
+    fn idle(&mut self) -> Option<Packet>;
+}
+
+/// The default monitor, for a network without one.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoMonitor;
+
+impl Monitor for NoMonitor {
+    fn receive(&mut self, _packet: Packet) {}
+
+    fn idle(&mut self) -> Option<Packet> {
+        None
+    }
+}
+
+/// Remembers the last packet it was sent and passes it on to address 0 when
+/// the network goes idle. It stops the network once it would pass on the same
+/// `y` twice in a row.
+#[derive(Debug, Default, Clone)]
+pub struct Nat {
+    last: Option<Packet>,
+    delivered: Vec<Packet>,
+}
+
+impl Nat {
+    pub fn new() -> Nat {
+        Nat::default()
+    }
+
+    /// The last packet the NAT was sent.
+    pub fn last(&self) -> Option<Packet> {
+        self.last
+    }
+
+    /// Every packet the NAT has sent to address 0.
+    pub fn delivered(&self) -> &[Packet] {
+        &self.delivered
+    }
+}
+
+impl Monitor for Nat {
+    fn receive(&mut self, packet: Packet) {
+        self.last = Some(packet);
+    }
+
+    fn idle(&mut self) -> Option<Packet> {
+        let packet = Packet {
+            destination: 0,
+            ..self.last?
+        };
+
+        if self.delivered.last().map(|last| last.y) == Some(packet.y) {
+            return None;
+        }
+
+        self.delivered.push(packet);
+        Some(packet)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum NetworkResult {
+    /// Every machine has halted.
+    Halted,
+    /// The network went idle and the monitor didn't wake it up.
+    Idle,
+    /// A packet was sent to an address with nothing there.
+    Undeliverable(Packet),
+    Fault {
+        address: i64,
+        error: MachineError,
+    },
+    /// A machine ran out of its budget.
+    BudgetExceeded {
+        address: i64,
+        limit: Limit,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum NetworkError {
+    /// The monitor was given an address that already has a machine at it.
+    AddressTaken(i64),
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetworkError::AddressTaken(address) => {
+                write!(f, "Monitor address {} is taken by a machine", address)
+            }
+        }
+    }
+}
+
+impl Error for NetworkError {}
+
+struct Node {
+    machine: OpCodeMachine,
+    queue: VecDeque<i64>,
+    output: Vec<i64>,
+    idle_reads: u32,
+    quiet_turns: u32,
+    halted: bool,
+}
+
+impl Node {
+    /// A machine that's stuck counts as idle even with packets waiting, as it
+    /// isn't going to read them.
+    fn is_idle(&self) -> bool {
+        self.halted
+            || self.quiet_turns >= IDLE_TURNS
+            || (self.queue.is_empty() && self.idle_reads >= IDLE_READS)
+    }
+}
+
+/// Machines at addresses 0 to N - 1 sending each other packets, one output
+/// triple of (destination, x, y) at a time. Each machine is given its address
+/// as its first input, and then the x and y of each packet sent to it, or
+/// `NO_PACKET` when there isn't one. Machines take turns in address order, so
+/// a run always plays out the same way.
+pub struct Network<M = NoMonitor> {
+    nodes: Vec<Node>,
+    monitor: Option<(i64, M)>,
+    quantum: usize,
+    sent: usize,
+}
+
+impl Network {
+    pub fn new(machines: Vec<OpCodeMachine>) -> Network {
+        let nodes = machines
+            .into_iter()
+            .enumerate()
+            .map(|(address, mut machine)| {
+                machine.input(address as i64);
+                Node {
+                    machine,
+                    queue: VecDeque::new(),
+                    output: Vec::new(),
+                    idle_reads: 0,
+                    quiet_turns: 0,
+                    halted: false,
+                }
+            })
+            .collect();
+
+        Network {
+            nodes,
+            monitor: None,
+            quantum: DEFAULT_QUANTUM,
+            sent: 0,
+        }
+    }
+
+    /// A network of `count` machines all running `program`.
+    pub fn with_program(program: &[i64], count: usize) -> Network {
+        Network::new(
+            (0..count)
+                .map(|_| OpCodeMachine::new(program.to_vec()))
+                .collect(),
+        )
+    }
+}
+
+impl<M: Monitor> Network<M> {
+    /// Puts `monitor` at `address`, unless a machine is already there.
+    pub fn with_monitor<N: Monitor>(
+        self,
+        address: i64,
+        monitor: N,
+    ) -> Result<Network<N>, NetworkError> {
+        if address >= 0 && (address as usize) < self.nodes.len() {
+            return Err(NetworkError::AddressTaken(address));
+        }
+
+        Ok(Network {
+            nodes: self.nodes,
+            monitor: Some((address, monitor)),
+            quantum: self.quantum,
+            sent: self.sent,
+        })
+    }
+
+    pub fn with_quantum(mut self, quantum: usize) -> Network<M> {
+        self.quantum = quantum.max(1);
+        self
+    }
+
+    pub fn monitor(&self) -> Option<&M> {
+        self.monitor.as_ref().map(|(_, monitor)| monitor)
+    }
+
+    pub fn machine(&self, address: usize) -> &OpCodeMachine {
+        &self.nodes[address].machine
+    }
+
+    /// The number of packets the machines and monitor have sent.
+    pub fn packets_sent(&self) -> usize {
+        self.sent
+    }
+
+    /// Sends a packet into the network from outside it.
+    pub fn send(&mut self, packet: Packet) -> Result<(), Packet> {
+        self.deliver(packet)
+    }
+
+    /// Runs until every machine halts, the network goes idle for good, or
+    /// something goes wrong. A machine that goes a hundred turns without
+    /// reading or sending anything counts as idle, so this always returns
+    /// unless the machines keep sending packets forever.
+    pub fn run(&mut self) -> NetworkResult {
+        loop {
+            for address in 0..self.nodes.len() {
+                if let Err(result) = self.run_node(address) {
+                    return result;
+                }
+            }
+
+            if self.nodes.iter().all(|node| node.halted) {
+                return NetworkResult::Halted;
+            }
+
+            if self.nodes.iter().all(Node::is_idle) {
+                let packet = match &mut self.monitor {
+                    Some((_, monitor)) => monitor.idle(),
+                    None => None,
+                };
+
+                match packet {
+                    Some(packet) => {
+                        self.sent += 1;
+                        if let Err(packet) = self.deliver(packet) {
+                            return NetworkResult::Undeliverable(packet);
+                        }
+                    }
+                    None => return NetworkResult::Idle,
+                }
+            }
+        }
+    }
+
+    /// Gives the machine at `address` its turn, which ends early if it asks
+    /// for input with nothing waiting.
+    fn run_node(&mut self, address: usize) -> Result<(), NetworkResult> {
+        let mut quiet = true;
+
+        for _ in 0..self.quantum {
+            let node = &mut self.nodes[address];
+            if node.halted {
+                return Ok(());
+            }
+
+            match node.machine.step() {
+                StepResult::Executed(step) => match step.effect {
+                    Effect::Output(value) => {
+                        node.output.push(value);
+                        node.idle_reads = 0;
+                        node.quiet_turns = 0;
+                        quiet = false;
+
+                        if node.output.len() == 3 {
+                            let packet = Packet {
+                                destination: node.output[0],
+                                x: node.output[1],
+                                y: node.output[2],
+                            };
+                            node.output.clear();
+
+                            self.sent += 1;
+                            self.deliver(packet).map_err(NetworkResult::Undeliverable)?;
+                        }
+                    }
+                    Effect::Halt => node.halted = true,
+                    _ => (),
+                },
+                StepResult::InputRequired => match node.queue.pop_front() {
+                    Some(value) => {
+                        node.machine.input(value);
+                        node.idle_reads = 0;
+                        node.quiet_turns = 0;
+                        quiet = false;
+                    }
+                    None => {
+                        node.machine.input(NO_PACKET);
+                        node.idle_reads += 1;
+                        return Ok(());
+                    }
+                },
+                StepResult::Halted => node.halted = true,
+                StepResult::Fault(error) => {
+                    return Err(NetworkResult::Fault {
+                        address: address as i64,
+                        error,
+                    })
+                }
+                StepResult::BudgetExceeded(limit) => {
+                    return Err(NetworkResult::BudgetExceeded {
+                        address: address as i64,
+                        limit,
+                    })
+                }
+            }
+        }
+
+        if quiet {
+            self.nodes[address].quiet_turns += 1;
+        }
+
+        Ok(())
+    }
+
+    fn deliver(&mut self, packet: Packet) -> Result<(), Packet> {
+        if let Some((address, monitor)) = &mut self.monitor {
+            if *address == packet.destination {
+                monitor.receive(packet);
+                return Ok(());
+            }
+        }
+
+        if packet.destination < 0 || packet.destination as usize >= self.nodes.len() {
+            return Err(packet);
+        }
+
+        let queue = &mut self.nodes[packet.destination as usize].queue;
+        queue.push_back(packet.x);
+        queue.push_back(packet.y);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::assemble;
+
+    // Adds 1 to the x of every packet it gets and passes it on to the next
+    // address, with the last machine sending to 255.
+    const RELAY: &str = "
+            IN   [address]
+            ADD  [address], #1, [next]
+            EQ   [next], #3, [last]
+            JF   [last], #loop
+            ADD  #255, #0, [next]
+        loop:
+            IN   [x]
+            EQ   [x], #-1, [empty]
+            JT   [empty], #loop
+            IN   [y]
+            ADD  [x], #1, [x]
+            OUT  [next]
+            OUT  [x]
+            OUT  [y]
+            JT   #1, #loop
+        address: data 0
+        next: data 0
+        last: data 0
+        empty: data 0
+        x: data 0
+        y: data 0";
+
+    // Sends one packet to 255 from address 0 and then reads forever.
+    const SENDER: &str = "
+            IN   [address]
+            JT   [address], #loop
+            OUT  #255
+            OUT  #7
+            OUT  #42
+        loop:
+            IN   [x]
+            JT   #1, #loop
+        address: data 0
+        x: data 0";
+
+    #[test]
+    fn relays_packets() {
+        let mut network = Network::with_program(&assemble(RELAY).unwrap(), 3)
+            .with_monitor(255, Nat::new())
+            .unwrap();
+        network
+            .send(Packet {
+                destination: 0,
+                x: 5,
+                y: 10,
+            })
+            .unwrap();
+
+        // The packet goes round once, the NAT sends it round again, and then
+        // stops because y hasn't changed.
+        assert_eq!(network.run(), NetworkResult::Idle);
+
+        let nat = network.monitor().unwrap();
+        assert_eq!(
+            nat.last(),
+            Some(Packet {
+                destination: 255,
+                x: 11,
+                y: 10
+            })
+        );
+        assert_eq!(nat.delivered().len(), 1);
+        assert_eq!(network.packets_sent(), 7);
+    }
+
+    #[test]
+    fn nat_stops_on_repeated_y() {
+        let mut network = Network::with_program(&assemble(SENDER).unwrap(), 4)
+            .with_monitor(255, Nat::new())
+            .unwrap();
+
+        assert_eq!(network.run(), NetworkResult::Idle);
+
+        let nat = network.monitor().unwrap();
+        let expected = Packet {
+            destination: 0,
+            x: 7,
+            y: 42,
+        };
+        assert_eq!(nat.delivered(), &[expected]);
+        assert_eq!(network.packets_sent(), 2);
+    }
+
+    #[test]
+    fn monitor_address_taken() {
+        let program = assemble("HLT").unwrap();
+
+        assert_eq!(
+            Network::with_program(&program, 3)
+                .with_monitor(2, Nat::new())
+                .err(),
+            Some(NetworkError::AddressTaken(2))
+        );
+        assert!(Network::with_program(&program, 3)
+            .with_monitor(3, Nat::new())
+            .is_ok());
+        assert!(Network::with_program(&program, 3)
+            .with_monitor(-1, Nat::new())
+            .is_ok());
+    }
+
+    #[test]
+    fn idle_without_monitor() {
+        let program = assemble("loop: IN [x]\nJT #1, #loop\nx: data 0").unwrap();
+        let mut network = Network::with_program(&program, 2);
+
+        assert_eq!(network.run(), NetworkResult::Idle);
+    }
+
+    #[test]
+    fn undeliverable() {
+        let mut network = Network::with_program(&assemble(SENDER).unwrap(), 2);
+
+        assert_eq!(
+            network.run(),
+            NetworkResult::Undeliverable(Packet {
+                destination: 255,
+                x: 7,
+                y: 42
+            })
+        );
+    }
+
+    #[test]
+    fn halts() {
+        let mut network = Network::with_program(&[3, 5, 4, 5, 99, 0], 3);
+
+        // Each machine outputs its address and halts without finishing a packet.
+        assert_eq!(network.run(), NetworkResult::Halted);
+        assert_eq!(network.packets_sent(), 0);
+    }
+
+    #[test]
+    fn faults() {
+        let mut network = Network::with_program(&[3, 7, 1005, 7, 6, 99, 77, 0], 2);
+
+        assert_eq!(
+            network.run(),
+            NetworkResult::Fault {
+                address: 1,
+                error: MachineError::InvalidOpCode {
+                    address: 6,
+                    value: 77
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn busy_machines_take_turns() {
+        // Address 0 spins forever without asking for input, while address 1
+        // sends a packet to the monitor, which must still get through.
+        let program = assemble(
+            "
+                IN   [address]
+                JF   [address], #spin
+                OUT  #255
+                OUT  #1
+                OUT  #2
+            wait:
+                IN   [address]
+                JT   #1, #wait
+            spin:
+                JT   #1, #spin
+            address: data 0",
+        )
+        .unwrap();
+
+        let mut network = Network::with_program(&program, 2)
+            .with_monitor(255, Nat::new())
+            .unwrap()
+            .with_quantum(10);
+
+        for _ in 0..5 {
+            for address in 0..2 {
+                network.run_node(address).unwrap();
+            }
+        }
+
+        assert_eq!(
+            network.monitor().unwrap().last(),
+            Some(Packet {
+                destination: 255,
+                x: 1,
+                y: 2
+            })
+        );
+        assert!(!network.nodes[0].is_idle());
+
+        // The NAT wakes address 0, which never reads its packet, and then
+        // stops the network.
+        assert_eq!(network.run(), NetworkResult::Idle);
+        assert!(network.nodes[0].is_idle());
+        assert_eq!(network.monitor().unwrap().delivered().len(), 1);
+    }
+
+    #[test]
+    fn stuck_machines_are_idle() {
+        let mut network = Network::with_program(&assemble("loop: JT #1, #loop").unwrap(), 2);
+
+        assert_eq!(network.run(), NetworkResult::Idle);
+        assert_eq!(network.nodes[0].quiet_turns, IDLE_TURNS);
+    }
+}
@@ -0,0 +1,97 @@
+use num_bigint::Sign;
+use std::convert::TryFrom;
+use std::fmt;
+
+pub use num_bigint::BigInt;
+
+/// The values an `OpCodeMachine` computes with. Arithmetic returns `None`
+/// when the result doesn't fit, which the machine reports as an overflow
+/// fault.
+pub trait Number: Clone + PartialEq + PartialOrd + fmt::Debug + fmt::Display + From<i64> {
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+    /// The value as an `i64` if it fits, for op codes and addresses.
+    fn to_i64(&self) -> Option<i64>;
+    fn is_zero(&self) -> bool;
+}
+
+impl Number for i64 {
+    fn checked_add(&self, other: &i64) -> Option<i64> {
+        i64::checked_add(*self, *other)
+    }
+
+    fn checked_mul(&self, other: &i64) -> Option<i64> {
+        i64::checked_mul(*self, *other)
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        Some(*self)
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+}
+
+impl Number for i128 {
+    fn checked_add(&self, other: &i128) -> Option<i128> {
+        i128::checked_add(*self, *other)
+    }
+
+    fn checked_mul(&self, other: &i128) -> Option<i128> {
+        i128::checked_mul(*self, *other)
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        i64::try_from(*self).ok()
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+}
+
+/// Never overflows, at the cost of an allocation for most values.
+impl Number for BigInt {
+    fn checked_add(&self, other: &BigInt) -> Option<BigInt> {
+        Some(self + other)
+    }
+
+    fn checked_mul(&self, other: &BigInt) -> Option<BigInt> {
+        Some(self * other)
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        i64::try_from(self).ok()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.sign() == Sign::NoSign
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checked() {
+        assert_eq!(Number::checked_mul(&(1i64 << 62), &2), None);
+        assert_eq!(Number::checked_mul(&(1i128 << 62), &2), Some(1 << 63));
+        assert_eq!(Number::checked_add(&i128::MAX, &1), None);
+        assert_eq!(
+            Number::checked_mul(&BigInt::from(i128::MAX), &BigInt::from(2)),
+            Some(BigInt::from(i128::MAX) * 2)
+        );
+    }
+
+    #[test]
+    fn to_i64() {
+        assert_eq!((-5i128).to_i64(), Some(-5));
+        assert_eq!((1i128 << 63).to_i64(), None);
+        assert_eq!(BigInt::from(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!((BigInt::from(1) << 64usize).to_i64(), None);
+        assert!(BigInt::from(0).is_zero());
+        assert!(!BigInt::from(-1).is_zero());
+    }
+}
@@ -0,0 +1,211 @@
+use crate::error::MachineError;
+use std::convert::TryFrom;
+
+pub(crate) fn get_op(value: i64, address: usize) -> Result<OpCode, MachineError> {
+    let ((p1, p2, p3), operation) = parse_operation(&value);
+
+    let mode = |mode: i64| {
+        ParameterMode::try_from(mode)
+            .map_err(|mode| MachineError::InvalidParameterMode { address, mode })
+    };
+
+    Ok(match operation {
+        1 => OpCode::Add(mode(p1)?, mode(p2)?, mode(p3)?),
+        2 => OpCode::Multiply(mode(p1)?, mode(p2)?, mode(p3)?),
+        3 => OpCode::Input(mode(p1)?),
+        4 => OpCode::Output(mode(p1)?),
+        5 => OpCode::JumpIfTrue(mode(p1)?, mode(p2)?),
+        6 => OpCode::JumpIfFalse(mode(p1)?, mode(p2)?),
+        7 => OpCode::LessThan(mode(p1)?, mode(p2)?, mode(p3)?),
+        8 => OpCode::Equals(mode(p1)?, mode(p2)?, mode(p3)?),
+        9 => OpCode::AdjustRelativeBase(mode(p1)?),
+        99 => OpCode::Halt,
+        _ => return Err(MachineError::InvalidOpCode { address, value }),
+    })
+}
+
+pub(crate) fn parse_operation(value: &i64) -> ((i64, i64, i64), i64) {
+    let operation = value % 100;
+
+    let p1 = value / 100 % 10;
+    let p2 = value / 1_000 % 10;
+    let p3 = value / 10_000 % 10;
+
+    ((p1, p2, p3), operation)
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum OpCode {
+    Add(ParameterMode, ParameterMode, ParameterMode),
+    Multiply(ParameterMode, ParameterMode, ParameterMode),
+    Input(ParameterMode),
+    Output(ParameterMode),
+    JumpIfTrue(ParameterMode, ParameterMode),
+    JumpIfFalse(ParameterMode, ParameterMode),
+    LessThan(ParameterMode, ParameterMode, ParameterMode),
+    Equals(ParameterMode, ParameterMode, ParameterMode),
+    AdjustRelativeBase(ParameterMode),
+    Halt,
+}
+
+impl OpCode {
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            OpCode::Add(..) => "ADD",
+            OpCode::Multiply(..) => "MUL",
+            OpCode::Input(..) => "IN",
+            OpCode::Output(..) => "OUT",
+            OpCode::JumpIfTrue(..) => "JT",
+            OpCode::JumpIfFalse(..) => "JF",
+            OpCode::LessThan(..) => "LT",
+            OpCode::Equals(..) => "EQ",
+            OpCode::AdjustRelativeBase(..) => "ARB",
+            OpCode::Halt => "HLT",
+        }
+    }
+
+    pub fn parameter_modes(&self) -> Vec<ParameterMode> {
+        match *self {
+            OpCode::Add(p1, p2, p3)
+            | OpCode::Multiply(p1, p2, p3)
+            | OpCode::LessThan(p1, p2, p3)
+            | OpCode::Equals(p1, p2, p3) => vec![p1, p2, p3],
+            OpCode::JumpIfTrue(p1, p2) | OpCode::JumpIfFalse(p1, p2) => vec![p1, p2],
+            OpCode::Input(p1) | OpCode::Output(p1) | OpCode::AdjustRelativeBase(p1) => vec![p1],
+            OpCode::Halt => vec![],
+        }
+    }
+
+    pub fn kind(&self) -> OpKind {
+        match self {
+            OpCode::Add(..) => OpKind::Add,
+            OpCode::Multiply(..) => OpKind::Multiply,
+            OpCode::Input(..) => OpKind::Input,
+            OpCode::Output(..) => OpKind::Output,
+            OpCode::JumpIfTrue(..) => OpKind::JumpIfTrue,
+            OpCode::JumpIfFalse(..) => OpKind::JumpIfFalse,
+            OpCode::LessThan(..) => OpKind::LessThan,
+            OpCode::Equals(..) => OpKind::Equals,
+            OpCode::AdjustRelativeBase(..) => OpKind::AdjustRelativeBase,
+            OpCode::Halt => OpKind::Halt,
+        }
+    }
+
+    /// Number of memory cells the instruction takes up, including itself.
+    pub fn size(&self) -> usize {
+        self.parameter_modes().len() + 1
+    }
+
+    /// The memory value for this op code, the inverse of `get_op`.
+    pub fn encode(&self) -> i64 {
+        let operation = match self {
+            OpCode::Add(..) => 1,
+            OpCode::Multiply(..) => 2,
+            OpCode::Input(..) => 3,
+            OpCode::Output(..) => 4,
+            OpCode::JumpIfTrue(..) => 5,
+            OpCode::JumpIfFalse(..) => 6,
+            OpCode::LessThan(..) => 7,
+            OpCode::Equals(..) => 8,
+            OpCode::AdjustRelativeBase(..) => 9,
+            OpCode::Halt => 99,
+        };
+
+        self.parameter_modes()
+            .iter()
+            .zip(&[100, 1_000, 10_000])
+            .fold(operation, |value, (mode, factor)| {
+                value + mode.code() * factor
+            })
+    }
+}
+
+/// An `OpCode` without its parameter modes.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum OpKind {
+    Add,
+    Multiply,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    AdjustRelativeBase,
+    Halt,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ParameterMode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+impl ParameterMode {
+    pub fn code(&self) -> i64 {
+        match self {
+            ParameterMode::Position => 0,
+            ParameterMode::Immediate => 1,
+            ParameterMode::Relative => 2,
+        }
+    }
+}
+
+impl TryFrom<i64> for ParameterMode {
+    type Error = i64;
+
+    fn try_from(value: i64) -> Result<ParameterMode, i64> {
+        match value {
+            0 => Ok(ParameterMode::Position),
+            1 => Ok(ParameterMode::Immediate),
+            2 => Ok(ParameterMode::Relative),
+            _ => Err(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    mod get_digits {
+        use super::super::*;
+
+        #[test]
+        fn basic() {
+            assert_eq!(parse_operation(&12345), ((3, 2, 1), 45));
+        }
+    }
+
+    mod get_op {
+        use super::super::*;
+
+        #[test]
+        fn modes() {
+            assert_eq!(
+                get_op(1002, 0),
+                Ok(OpCode::Multiply(
+                    ParameterMode::Position,
+                    ParameterMode::Immediate,
+                    ParameterMode::Position
+                ))
+            );
+        }
+
+        #[test]
+        fn size() {
+            assert_eq!(get_op(21101, 0).unwrap().size(), 4);
+            assert_eq!(get_op(1105, 0).unwrap().size(), 3);
+            assert_eq!(get_op(99, 0).unwrap().size(), 1);
+        }
+
+        #[test]
+        fn encode() {
+            for value in &[
+                1, 1002, 21101, 3, 203, 104, 1105, 2106, 1107, 20008, 109, 99,
+            ] {
+                assert_eq!(get_op(*value, 0).unwrap().encode(), *value);
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use crate::builder::MachineBuilder;
+use crate::io::IterIo;
+use crate::machine::MachineResult;
+use rayon::prelude::*;
+use std::ops::RangeInclusive;
+
+/// A value written over a program before it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Patch {
+    pub address: usize,
+    pub value: i64,
+}
+
+impl Patch {
+    pub fn new(address: usize, value: i64) -> Patch {
+        Patch { address, value }
+    }
+
+    /// The first of the two inputs patched into the day 2 gravity assist
+    /// program.
+    pub fn noun(value: i64) -> Patch {
+        Patch::new(1, value)
+    }
+
+    /// The second of the day 2 inputs.
+    pub fn verb(value: i64) -> Patch {
+        Patch::new(2, value)
+    }
+
+    /// Sets address 0 to 2, which wakes up the day 17 robot and gives free
+    /// play in the day 13 arcade game.
+    pub fn wake_up() -> Patch {
+        Patch::new(0, 2)
+    }
+}
+
+/// One dimension of a patch search: the values to try at an address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Axis {
+    pub address: usize,
+    pub values: RangeInclusive<i64>,
+}
+
+impl Axis {
+    pub fn new(address: usize, values: RangeInclusive<i64>) -> Axis {
+        Axis { address, values }
+    }
+}
+
+/// What a patched program has to halt with to be a match.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    /// The last value the program output.
+    Output(i64),
+    /// The value left at an address.
+    Memory { address: usize, value: i64 },
+}
+
+/// Tries every pair of values from `first` and `second`, patched into the
+/// machine `builder` makes, returning the pair whose run halts with `target`.
+/// The pairs are run in parallel, but the answer is the one a search in
+/// order would find first. Runs that fault, need input or go over the
+/// builder's budget don't match, so give the builder a budget if some
+/// patches could loop forever.
+pub fn search_patches(
+    builder: &MachineBuilder,
+    first: &Axis,
+    second: &Axis,
+    target: Target,
+) -> Option<(i64, i64)> {
+    first
+        .values
+        .clone()
+        .into_par_iter()
+        .flat_map(|x| second.values.clone().into_par_iter().map(move |y| (x, y)))
+        .find_first(|&(x, y)| {
+            let mut machine = builder
+                .clone()
+                .patch(Patch::new(first.address, x))
+                .patch(Patch::new(second.address, y))
+                .build();
+
+            let mut io = IterIo::new(Vec::new());
+            if machine.run_with(&mut io) != MachineResult::Halt {
+                return false;
+            }
+
+            match target {
+                Target::Output(value) => io.outputs().last() == Some(&value),
+                Target::Memory { address, value } => machine.memory().read(address) == value,
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::assemble;
+    use crate::budget::Budget;
+    use crate::machine::OpCodeMachine;
+
+    // Adds the values at the noun and verb addresses, then multiplies by the
+    // one at address 12, leaving the result at address 0.
+    const GRAVITY_ASSIST: [i64; 13] = [1, 0, 0, 3, 2, 3, 12, 0, 99, 30, 40, 50, 3];
+
+    #[test]
+    fn patches() {
+        let mut machine = OpCodeMachine::builder(GRAVITY_ASSIST.to_vec())
+            .patch(Patch::noun(9))
+            .patch(Patch::verb(10))
+            .build();
+
+        assert_eq!(machine.run(), MachineResult::Halt);
+        assert_eq!(machine.memory().read(0), 210);
+        assert_eq!(machine.memory().read(3), 70);
+    }
+
+    #[test]
+    fn later_patches_win() {
+        let mut machine = OpCodeMachine::builder(vec![104, 1, 99])
+            .patches(vec![Patch::new(1, 2), Patch::new(1, 3), Patch::new(5, 7)])
+            .build();
+
+        assert_eq!(machine.run(), MachineResult::Output(3));
+        assert_eq!(machine.run(), MachineResult::Halt);
+        assert_eq!(machine.memory().read(5), 7);
+    }
+
+    #[test]
+    fn wake_up() {
+        // Turns the add into a multiply.
+        let mut machine = OpCodeMachine::builder(vec![1, 0, 0, 0, 99])
+            .patch(Patch::wake_up())
+            .build();
+
+        assert_eq!(machine.run(), MachineResult::Halt);
+        assert_eq!(machine.memory().read(0), 4);
+    }
+
+    #[test]
+    fn search_memory() {
+        let builder = OpCodeMachine::builder(GRAVITY_ASSIST.to_vec());
+        let axis = Axis::new(1, 0..=12);
+
+        let target = Target::Memory {
+            address: 0,
+            value: 3 * (40 + 50),
+        };
+        assert_eq!(
+            search_patches(&builder, &axis, &Axis::new(2, 0..=12), target),
+            Some((10, 11))
+        );
+
+        let target = Target::Memory {
+            address: 0,
+            value: 1,
+        };
+        assert_eq!(
+            search_patches(&builder, &axis, &Axis::new(2, 0..=12), target),
+            None
+        );
+    }
+
+    #[test]
+    fn search_output() {
+        // Outputs the product of the operands at addresses 1 and 2, unless
+        // it's 0, when it loops forever.
+        let program = assemble(
+            "
+                MUL  #0, #0, [p]
+                JF   [p], #spin
+                OUT  [p]
+                HLT
+            spin:
+                JT   #1, #spin
+            p:  data 0",
+        )
+        .unwrap();
+        let builder = OpCodeMachine::builder(program).budget(Budget::default().steps_per_run(100));
+
+        assert_eq!(
+            search_patches(
+                &builder,
+                &Axis::new(1, -5..=5),
+                &Axis::new(2, -5..=5),
+                Target::Output(15)
+            ),
+            Some((-5, -3))
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use crate::disassembler::decode;
+use crate::machine::Step;
+use crate::memory::Memory;
+use crate::trace::Tracer;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Counts how often each address and each kind of instruction is executed.
+#[derive(Debug, Default, Clone)]
+pub struct Profiler {
+    hits: Vec<u64>,
+    op_codes: BTreeMap<&'static str, u64>,
+    total: u64,
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn hits(&self, address: usize) -> u64 {
+        self.hits.get(address).copied().unwrap_or(0)
+    }
+
+    /// Executed instruction counts keyed by mnemonic.
+    pub fn op_codes(&self) -> &BTreeMap<&'static str, u64> {
+        &self.op_codes
+    }
+
+    /// The most executed addresses, busiest first.
+    pub fn hot_spots(&self, limit: usize) -> Vec<(usize, u64)> {
+        let mut spots = self
+            .hits
+            .iter()
+            .enumerate()
+            .filter(|(_, &hits)| hits > 0)
+            .map(|(address, &hits)| (address, hits))
+            .collect::<Vec<(usize, u64)>>();
+
+        spots.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        spots.truncate(limit);
+        spots
+    }
+
+    /// Builds a report of the hottest addresses, disassembled from `memory`,
+    /// followed by the totals for each kind of instruction.
+    pub fn report(&self, memory: &Memory, limit: usize) -> String {
+        let mut report = String::new();
+        let percent = |hits: u64| hits as f64 * 100.0 / self.total.max(1) as f64;
+
+        writeln!(report, "{} instructions executed", self.total).unwrap();
+        writeln!(report).unwrap();
+        writeln!(report, "{:>10} {:>7}  instruction", "hits", "%").unwrap();
+        for (address, hits) in self.hot_spots(limit) {
+            let line = decode(&memory.read_range(address, 4), address);
+            writeln!(report, "{:>10} {:>6.2}% {}", hits, percent(hits), line).unwrap();
+        }
+
+        writeln!(report).unwrap();
+        writeln!(report, "{:>10} {:>7}  op code", "hits", "%").unwrap();
+        let mut op_codes = self.op_codes.iter().collect::<Vec<_>>();
+        op_codes.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for (mnemonic, &hits) in op_codes {
+            writeln!(report, "{:>10} {:>6.2}%  {}", hits, percent(hits), mnemonic).unwrap();
+        }
+
+        report
+    }
+}
+
+impl Tracer for Profiler {
+    fn trace(&mut self, step: &Step) {
+        if step.address >= self.hits.len() {
+            self.hits.resize(step.address + 1, 0);
+        }
+
+        self.hits[step.address] += 1;
+        *self.op_codes.entry(step.op_code.mnemonic()).or_insert(0) += 1;
+        self.total += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::*;
+
+    // Counts down from 3, outputting each value.
+    const COUNTDOWN: [i64; 12] = [4, 11, 1001, 11, -1, 11, 1005, 11, 0, 99, 0, 3];
+
+    #[test]
+    fn counts() {
+        let mut machine = OpCodeMachine::new(COUNTDOWN.to_vec()).with_tracer(Profiler::new());
+        while machine.run() != MachineResult::Halt {}
+
+        let profiler = machine.tracer();
+        assert_eq!(profiler.total(), 10);
+        assert_eq!(profiler.hits(0), 3);
+        assert_eq!(profiler.hits(2), 3);
+        assert_eq!(profiler.hits(9), 1);
+        assert_eq!(profiler.hits(100), 0);
+        assert_eq!(profiler.op_codes()["JT"], 3);
+        assert_eq!(profiler.hot_spots(2), vec![(0, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn report() {
+        let mut machine = OpCodeMachine::new(COUNTDOWN.to_vec()).with_tracer(Profiler::new());
+        while machine.run() != MachineResult::Halt {}
+
+        let report = machine.tracer().report(machine.memory(), 1);
+        assert!(report.starts_with("10 instructions executed\n"));
+        assert!(report.contains("         3  30.00%     0: OUT  [11]\n"));
+        assert!(report.contains("         1  10.00%  HLT\n"));
+    }
+}
@@ -0,0 +1,173 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::{env, process};
+
+#[derive(Debug)]
+pub enum ProgramError {
+    Io(io::Error),
+    /// There were no values at all.
+    Empty,
+    /// Two commas with nothing between them, or a comma at the end.
+    MissingValue {
+        line: usize,
+        column: usize,
+    },
+    InvalidToken {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+}
+
+impl fmt::Display for ProgramError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProgramError::Io(error) => write!(f, "{}", error),
+            ProgramError::Empty => write!(f, "The program is empty"),
+            ProgramError::MissingValue { line, column } => {
+                write!(f, "Missing value at line {}, column {}", line, column)
+            }
+            ProgramError::InvalidToken {
+                line,
+                column,
+                token,
+            } => write!(
+                f,
+                "'{}' at line {}, column {} is not a number",
+                token, line, column
+            ),
+        }
+    }
+}
+
+impl Error for ProgramError {}
+
+impl From<io::Error> for ProgramError {
+    fn from(error: io::Error) -> ProgramError {
+        ProgramError::Io(error)
+    }
+}
+
+/// Parses a comma separated program, as given by the puzzle inputs. Values
+/// can be spread over as many lines as needed.
+pub fn parse_program(source: &str) -> Result<Vec<i64>, ProgramError> {
+    if source.trim().is_empty() {
+        return Err(ProgramError::Empty);
+    }
+
+    let mut program = Vec::new();
+    let mut token = String::new();
+    // Whitespace after the token, which is only part of it if more follows.
+    let mut gap = String::new();
+    // Where the token started, or if it's empty, where it would have.
+    let mut start = (1, 1);
+    let (mut line, mut column) = (1, 1);
+
+    for c in source.chars().chain(Some(',')) {
+        if c == ',' {
+            if token.is_empty() {
+                return Err(ProgramError::MissingValue {
+                    line: start.0,
+                    column: start.1,
+                });
+            }
+
+            let value = token.parse().map_err(|_| ProgramError::InvalidToken {
+                line: start.0,
+                column: start.1,
+                token: token.split_off(0),
+            })?;
+            program.push(value);
+            token.clear();
+            gap.clear();
+        } else if c.is_whitespace() {
+            if !token.is_empty() {
+                gap.push(c);
+            }
+        } else {
+            token.push_str(&gap);
+            token.push(c);
+            gap.clear();
+        }
+
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+
+        if token.is_empty() {
+            start = (line, column);
+        }
+    }
+
+    Ok(program)
+}
+
+pub fn load_program<P: AsRef<Path>>(path: P) -> Result<Vec<i64>, ProgramError> {
+    parse_program(&fs::read_to_string(path)?)
+}
+
+pub fn read_program<R: Read>(mut reader: R) -> Result<Vec<i64>, ProgramError> {
+    let mut source = String::new();
+    reader.read_to_string(&mut source)?;
+    parse_program(&source)
+}
+
+/// Loads the program named by the first command line argument, reading stdin
+/// for `-`, or parses `default` if there's no argument. Exits with a message
+/// if the program can't be loaded.
+pub fn program_from_args(default: &str) -> Vec<i64> {
+    let (name, program) = match env::args().nth(1) {
+        Some(path) if path == "-" => ("stdin".to_string(), read_program(io::stdin())),
+        Some(path) => (path.clone(), load_program(&path)),
+        None => ("the built-in input".to_string(), parse_program(default)),
+    };
+
+    program.unwrap_or_else(|error| {
+        eprintln!("Unable to load {}: {}", name, error);
+        process::exit(1);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_program("1,2,-3,99").unwrap(), vec![1, 2, -3, 99]);
+        assert_eq!(
+            parse_program("  1, 2,\n 3\r\n,4\n\n").unwrap(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(
+            read_program("104,7,99\n".as_bytes()).unwrap(),
+            vec![104, 7, 99]
+        );
+    }
+
+    fn error(source: &str) -> String {
+        parse_program(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(error(" \n"), "The program is empty");
+        assert_eq!(
+            error("1,2,\n3,x4,5"),
+            "'x4' at line 2, column 3 is not a number"
+        );
+        assert_eq!(error("1,2 3"), "'2 3' at line 1, column 3 is not a number");
+        assert_eq!(error("1,,2"), "Missing value at line 1, column 3");
+        assert_eq!(error("1,2,\n"), "Missing value at line 2, column 1");
+        assert!(matches!(
+            load_program("does/not/exist"),
+            Err(ProgramError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,418 @@
+use crate::machine::OpCodeMachine;
+use crate::memory::MemoryModel;
+use crate::trace::{NoTracer, Tracer};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"ICSN";
+
+/// The complete state of an `OpCodeMachine`, apart from its tracer.
+/// `memory` holds the dense part of memory and `sparse` any values beyond it.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub memory: Vec<i64>,
+    #[serde(default)]
+    pub sparse: Vec<(usize, i64)>,
+    #[serde(default)]
+    pub memory_model: MemoryModel,
+    pub inputs: Vec<i64>,
+    pub pointer: usize,
+    pub relative_base: isize,
+    pub is_complete: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Binary,
+    Json,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    UnsupportedVersion(u64),
+    Truncated,
+    InvalidValue(&'static str),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Io(error) => write!(f, "{}", error),
+            SnapshotError::Json(error) => write!(f, "Invalid JSON snapshot: {}", error),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported snapshot version {}", version)
+            }
+            SnapshotError::Truncated => write!(f, "Snapshot is truncated"),
+            SnapshotError::InvalidValue(field) => write!(f, "Invalid value for {}", field),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(error: io::Error) -> SnapshotError {
+        SnapshotError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(error: serde_json::Error) -> SnapshotError {
+        SnapshotError::Json(error)
+    }
+}
+
+impl Snapshot {
+    /// Version 1 had no memory model or sparse values, which reads back as
+    /// dense memory.
+    pub const VERSION: u32 = 2;
+
+    /// Encodes the snapshot as a magic number, a version and then each field,
+    /// with integers as zigzag LEB128 varints so small values take one byte.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        write_varint(&mut bytes, Snapshot::VERSION as u64);
+        bytes.push(self.is_complete as u8);
+        write_varint(&mut bytes, self.pointer as u64);
+        write_varint(&mut bytes, zigzag(self.relative_base as i64));
+
+        for values in &[&self.memory, &self.inputs] {
+            write_varint(&mut bytes, values.len() as u64);
+            for value in values.iter() {
+                write_varint(&mut bytes, zigzag(*value));
+            }
+        }
+
+        match self.memory_model {
+            MemoryModel::Dense => bytes.push(0),
+            MemoryModel::Hybrid { dense_limit } => {
+                bytes.push(1);
+                write_varint(&mut bytes, dense_limit as u64);
+            }
+        }
+
+        write_varint(&mut bytes, self.sparse.len() as u64);
+        for (address, value) in &self.sparse {
+            write_varint(&mut bytes, *address as u64);
+            write_varint(&mut bytes, zigzag(*value));
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Snapshot, SnapshotError> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(SnapshotError::InvalidValue("magic number"));
+        }
+
+        let mut reader = Reader {
+            bytes,
+            position: MAGIC.len(),
+        };
+
+        let version = reader.varint()?;
+        if version == 0 || version > Snapshot::VERSION as u64 {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        let is_complete = match reader.byte()? {
+            0 => false,
+            1 => true,
+            _ => return Err(SnapshotError::InvalidValue("is_complete")),
+        };
+        let pointer = reader.varint()? as usize;
+        let relative_base = unzigzag(reader.varint()?) as isize;
+        let memory = reader.values()?;
+        let inputs = reader.values()?;
+
+        let mut memory_model = MemoryModel::Dense;
+        let mut sparse = Vec::new();
+        if version >= 2 {
+            memory_model = match reader.byte()? {
+                0 => MemoryModel::Dense,
+                1 => MemoryModel::Hybrid {
+                    dense_limit: reader.varint()? as usize,
+                },
+                _ => return Err(SnapshotError::InvalidValue("memory model")),
+            };
+
+            for _ in 0..reader.count()? {
+                sparse.push((reader.varint()? as usize, unzigzag(reader.varint()?)));
+            }
+        }
+
+        if reader.position != bytes.len() {
+            return Err(SnapshotError::InvalidValue("trailing bytes"));
+        }
+
+        Ok(Snapshot {
+            version: Snapshot::VERSION,
+            memory,
+            sparse,
+            memory_model,
+            inputs,
+            pointer,
+            relative_base,
+            is_complete,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Snapshots always serialize")
+    }
+
+    pub fn from_json(json: &str) -> Result<Snapshot, SnapshotError> {
+        let mut snapshot: Snapshot = serde_json::from_str(json)?;
+
+        if snapshot.version == 0 || snapshot.version > Snapshot::VERSION {
+            return Err(SnapshotError::UnsupportedVersion(snapshot.version.into()));
+        }
+
+        // Older versions read back as the current one, as they do in binary.
+        snapshot.version = Snapshot::VERSION;
+        Ok(snapshot)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P, format: Format) -> Result<(), SnapshotError> {
+        match format {
+            Format::Binary => fs::write(path, self.to_bytes())?,
+            Format::Json => fs::write(path, self.to_json())?,
+        }
+
+        Ok(())
+    }
+
+    /// Reads a snapshot in either format, telling them apart by the magic number.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Snapshot, SnapshotError> {
+        let bytes = fs::read(path)?;
+
+        if bytes.starts_with(MAGIC) {
+            Snapshot::from_bytes(&bytes)
+        } else {
+            let json = String::from_utf8(bytes)
+                .map_err(|_| SnapshotError::InvalidValue("JSON encoding"))?;
+            Snapshot::from_json(&json)
+        }
+    }
+}
+
+impl<T: Tracer> OpCodeMachine<T> {
+    pub fn save<P: AsRef<Path>>(&self, path: P, format: Format) -> Result<(), SnapshotError> {
+        self.snapshot().save(path, format)
+    }
+}
+
+impl OpCodeMachine<NoTracer> {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<OpCodeMachine, SnapshotError> {
+        OpCodeMachine::from_snapshot(Snapshot::load(path)?)
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, SnapshotError> {
+        let byte = *self
+            .bytes
+            .get(self.position)
+            .ok_or(SnapshotError::Truncated)?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn varint(&mut self) -> Result<u64, SnapshotError> {
+        let mut value = 0u64;
+
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(SnapshotError::InvalidValue("varint"))
+    }
+
+    fn count(&mut self) -> Result<usize, SnapshotError> {
+        let count = self.varint()? as usize;
+
+        // Every value takes at least a byte, which stops a corrupt count
+        // from reserving huge amounts of memory.
+        if count > self.bytes.len() - self.position {
+            return Err(SnapshotError::Truncated);
+        }
+
+        Ok(count)
+    }
+
+    fn values(&mut self) -> Result<Vec<i64>, SnapshotError> {
+        (0..self.count()?)
+            .map(|_| Ok(unzigzag(self.varint()?)))
+            .collect()
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+
+    bytes.push(value as u8);
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::MachineResult;
+    use std::env;
+    use std::process;
+
+    // Outputs the sum of each pair of inputs, forever.
+    const ADDER: [i64; 15] = [3, 13, 3, 14, 1, 13, 14, 13, 4, 13, 1105, 1, 0, 0, 0];
+
+    fn outputs(machine: &mut OpCodeMachine, inputs: &[i64]) -> Vec<i64> {
+        let mut outputs = Vec::new();
+
+        for input in inputs {
+            machine.input(*input);
+        }
+
+        while let MachineResult::Output(value) = machine.run() {
+            outputs.push(value);
+        }
+
+        outputs
+    }
+
+    fn machine() -> OpCodeMachine {
+        let mut machine = OpCodeMachine::new(ADDER.to_vec());
+        outputs(&mut machine, &[3, 4, 5]);
+        machine.input(6);
+        machine
+    }
+
+    #[test]
+    fn zigzag_round_trip() {
+        for value in &[0, 1, -1, 63, -64, i64::MAX, i64::MIN] {
+            assert_eq!(unzigzag(zigzag(*value)), *value);
+        }
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let snapshot = machine().snapshot();
+        let bytes = snapshot.to_bytes();
+
+        assert_eq!(&bytes[..4], MAGIC);
+        assert_eq!(Snapshot::from_bytes(&bytes).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let snapshot = machine().snapshot();
+        let json = snapshot.to_json();
+
+        assert!(json.contains("\"relative_base\": 0"));
+        assert_eq!(Snapshot::from_json(&json).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn restored_outputs_match() {
+        for (name, format) in &[("binary", Format::Binary), ("json", Format::Json)] {
+            let path = env::temp_dir().join(format!("intcode_snapshot_{}_{}", name, process::id()));
+
+            let mut original = machine();
+            original.save(&path, *format).unwrap();
+            let mut restored = OpCodeMachine::load(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(restored.inputs(), &[6]);
+            assert_eq!(
+                outputs(&mut restored, &[7, 8]),
+                outputs(&mut original, &[7, 8])
+            );
+            assert_eq!(restored.memory(), original.memory());
+        }
+    }
+
+    #[test]
+    fn version_1() {
+        let bytes = b"ICSN\x01\x00\x00\x00\x01\xc6\x01\x00";
+        let snapshot = Snapshot::from_bytes(bytes).unwrap();
+
+        assert_eq!(snapshot.memory, vec![99]);
+        assert_eq!(snapshot.memory_model, MemoryModel::Dense);
+        assert!(snapshot.sparse.is_empty());
+
+        let json = r#"{"version": 1, "memory": [99], "inputs": [], "pointer": 0,
+            "relative_base": 0, "is_complete": false}"#;
+        assert_eq!(Snapshot::from_json(json).unwrap(), snapshot);
+        assert_eq!(snapshot.version, Snapshot::VERSION);
+    }
+
+    #[test]
+    fn invalid() {
+        let bytes = machine().snapshot().to_bytes();
+
+        assert!(matches!(
+            Snapshot::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(SnapshotError::Truncated)
+        ));
+        assert!(matches!(
+            Snapshot::from_bytes(b"ICSN\x03"),
+            Err(SnapshotError::UnsupportedVersion(3))
+        ));
+        assert!(matches!(
+            Snapshot::from_bytes(b"ICSN\x81\x80\x80\x80\x10"),
+            Err(SnapshotError::UnsupportedVersion(0x1_0000_0001))
+        ));
+        assert!(matches!(
+            Snapshot::from_bytes(b"nope"),
+            Err(SnapshotError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            Snapshot::from_json("{\"version\": 1}"),
+            Err(SnapshotError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_sparse() {
+        let mut snapshot = machine().snapshot();
+        snapshot.sparse = vec![(1 << 20, 5)];
+        assert!(matches!(
+            OpCodeMachine::from_snapshot(snapshot.clone()),
+            Err(SnapshotError::InvalidValue("sparse address"))
+        ));
+
+        snapshot.memory_model = MemoryModel::hybrid();
+        assert!(OpCodeMachine::from_snapshot(snapshot.clone()).is_ok());
+
+        snapshot.sparse = vec![(3, 5)];
+        assert!(matches!(
+            OpCodeMachine::from_snapshot(snapshot),
+            Err(SnapshotError::InvalidValue("sparse address"))
+        ));
+    }
+}
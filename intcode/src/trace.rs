@@ -0,0 +1,123 @@
+use crate::machine::{Effect, Step};
+use crate::numeric::Number;
+use std::fmt;
+use std::io;
+
+/// Receives every instruction an `OpCodeMachine` of `N`s executes.
+pub trait Tracer<N = i64> {
+    fn trace(&mut self, step: &Step<N>);
+}
+
+/// The default tracer, which does nothing and compiles away entirely.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoTracer;
+
+impl<N> Tracer<N> for NoTracer {
+    #[inline(always)]
+    fn trace(&mut self, _step: &Step<N>) {}
+}
+
+impl<N: Clone> Tracer<N> for Vec<Step<N>> {
+    fn trace(&mut self, step: &Step<N>) {
+        self.push(step.clone());
+    }
+}
+
+/// Writes each step as a line of text, e.g. `   12: ADD  3, 4, 20  [20] = 7`.
+#[derive(Debug, Clone)]
+pub struct WriteTracer<W: io::Write> {
+    writer: W,
+}
+
+impl<W: io::Write> WriteTracer<W> {
+    pub fn new(writer: W) -> WriteTracer<W> {
+        WriteTracer { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: io::Write, N: Number> Tracer<N> for WriteTracer<W> {
+    fn trace(&mut self, step: &Step<N>) {
+        // A trace is diagnostic output, so a failed write shouldn't stop the machine.
+        let _ = writeln!(self.writer, "{}", step);
+    }
+}
+
+impl<N: fmt::Display> fmt::Display for Step<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let count = self.op_code.parameter_modes().len();
+        if count == 0 {
+            return write!(f, "{:>5}: {}", self.address, self.op_code.mnemonic());
+        }
+
+        let operands = self.operands[..count]
+            .iter()
+            .map(|o| o.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        write!(
+            f,
+            "{:>5}: {:<4} {}",
+            self.address,
+            self.op_code.mnemonic(),
+            operands
+        )?;
+
+        match &self.effect {
+            Effect::Write { address, value } | Effect::Input { address, value } => {
+                write!(f, "  [{}] = {}", address, value)
+            }
+            Effect::Output(value) => write!(f, "  out {}", value),
+            Effect::Jump(Some(target)) => write!(f, "  -> {}", target),
+            Effect::Jump(None) => Ok(()),
+            Effect::RelativeBase(base) => write!(f, "  rb = {}", base),
+            Effect::Halt => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::*;
+
+    #[test]
+    fn records_steps() {
+        let mut machine =
+            OpCodeMachine::new(vec![1101, 3, 4, 7, 4, 7, 99, 0]).with_tracer(Vec::new());
+
+        assert_eq!(machine.run(), MachineResult::Output(7));
+        assert_eq!(machine.run(), MachineResult::Halt);
+
+        let steps = machine.tracer();
+        assert_eq!(steps.len(), 3);
+        assert_eq!(steps[0].operands, [3, 4, 7]);
+        assert_eq!(
+            steps[0].effect,
+            Effect::Write {
+                address: 7,
+                value: 7
+            }
+        );
+        assert_eq!(steps[1].operands, [7, 0, 0]);
+        assert_eq!(steps[2].effect, Effect::Halt);
+    }
+
+    #[test]
+    fn writes_lines() {
+        let mut machine = OpCodeMachine::new(vec![1101, 3, 4, 7, 1005, 7, 9, 0, 0, 99])
+            .with_tracer(WriteTracer::new(Vec::new()));
+
+        assert_eq!(machine.run(), MachineResult::Halt);
+
+        let text = String::from_utf8(machine.tracer().clone().into_inner()).unwrap();
+        assert_eq!(
+            text,
+            "    0: ADD  3, 4, 7  [7] = 7\n    4: JT   7, 9  -> 9\n    9: HLT\n"
+        );
+    }
+}